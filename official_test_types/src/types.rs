@@ -3,7 +3,8 @@ use hex::FromHex;
 use primitive_types::{H256, U256};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::{collections::BTreeMap, fmt};
-use crate::util::{Hex, u256_to_h256};
+use crate::util::{Hex, deserialize_optional_address, u256_to_h256};
+use ethereum_evm::Hardfork;
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
 struct WrappedU256(U256);
@@ -71,33 +72,40 @@ pub struct TestStateMulti {
 impl TestStateMulti {
     pub fn tests(&self) -> Vec<TestState> {
         let mut tests = Vec::new();
-        for (fork, post_states) in &self.post {
-            if fork == "Berlin" {
-                let mut new_tests: Vec<TestState> = post_states
-                    .iter()
-                    .enumerate()
-                    .map(|(index, post_state)| TestState {
-                        info: self.info.clone(),
-                        env: self.env.clone(),
-                        fork: fork.clone(),
-                        post: post_state.clone(),
-                        pre: self.pre.clone(),
-                        transaction: TestTransaction {
-                            data: self.transaction.data[post_state.indexes.data].0.clone(),
-                            gas_limit: self.transaction.gas_limit[post_state.indexes.gas],
-                            gas_price: self.transaction.gas_price,
-                            max_fee_per_gas: self.transaction.max_fee_per_gas,
-                            max_priority_fee_per_gas: self.transaction.max_priority_fee_per_gas,
-                            nonce: self.transaction.nonce,
-                            secret_key: self.transaction.secret_key,
-                            sender: self.transaction.sender,
-                            to: self.transaction.to,
-                            value: self.transaction.value[post_state.indexes.value],
-                        },
-                    })
-                    .collect();
-                tests.append(&mut new_tests);
-            }
+        // Fixtures also cover forks that aren't supported yet, no tests are made for those
+        let supported = self.post.iter().filter(|(fork, _)| fork.parse::<Hardfork>().is_ok());
+        for (fork, post_states) in supported {
+            let mut new_tests: Vec<TestState> = post_states
+                .iter()
+                .enumerate()
+                .map(|(index, post_state)| TestState {
+                    info: self.info.clone(),
+                    env: self.env.clone(),
+                    fork: fork.clone(),
+                    post: post_state.clone(),
+                    pre: self.pre.clone(),
+                    transaction: TestTransaction {
+                        access_list: self.transaction.access_lists
+                            .get(post_state.indexes.data)
+                            .cloned()
+                            .flatten()
+                            .unwrap_or_default(),
+                        authorization_list: self.transaction.authorization_list.clone(),
+                        blob_versioned_hashes: self.transaction.blob_versioned_hashes.clone(),
+                        data: self.transaction.data[post_state.indexes.data].0.clone(),
+                        gas_limit: self.transaction.gas_limit[post_state.indexes.gas],
+                        gas_price: self.transaction.gas_price,
+                        max_fee_per_gas: self.transaction.max_fee_per_gas,
+                        max_priority_fee_per_gas: self.transaction.max_priority_fee_per_gas,
+                        nonce: self.transaction.nonce,
+                        secret_key: self.transaction.secret_key,
+                        sender: self.transaction.sender,
+                        to: self.transaction.to,
+                        value: self.transaction.value[post_state.indexes.value],
+                    },
+                })
+                .collect();
+            tests.append(&mut new_tests);
         }
         tests
     }
@@ -186,9 +194,33 @@ impl TestContract{
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestAccessListItem {
+    pub address: U256,
+    pub storage_keys: Vec<U256>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestAuthorization {
+    pub chain_id: U256,
+    pub address: U256,
+    pub nonce: U256,
+    pub y_parity: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestTransactionMulti {
+    #[serde(default)]
+    pub access_lists: Vec<Option<Vec<TestAccessListItem>>>,
+    #[serde(default)]
+    pub authorization_list: Vec<TestAuthorization>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<H256>,
     pub data: Vec<Hex>,
     pub gas_limit: Vec<U256>,
     pub gas_price: Option<U256>,
@@ -196,7 +228,8 @@ pub struct TestTransactionMulti {
     pub max_priority_fee_per_gas: Option<U256>,
     pub nonce: U256,
     pub secret_key: U256,
-    pub to: U256,
+    #[serde(deserialize_with = "deserialize_optional_address")]
+    pub to: Option<U256>,
     pub sender: U256,
     pub value: Vec<U256>,
 }
@@ -204,6 +237,9 @@ pub struct TestTransactionMulti {
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestTransaction {
+    pub access_list: Vec<TestAccessListItem>,
+    pub authorization_list: Vec<TestAuthorization>,
+    pub blob_versioned_hashes: Vec<H256>,
     pub data: Vec<u8>,
     pub gas_limit: U256,
     pub gas_price: Option<U256>,
@@ -212,6 +248,7 @@ pub struct TestTransaction {
     pub nonce: U256,
     pub secret_key: U256,
     pub sender: U256,
-    pub to: U256,
+    #[serde(deserialize_with = "deserialize_optional_address")]
+    pub to: Option<U256>,
     pub value: U256,
 }
@@ -68,3 +68,21 @@ where
 
     serializer.serialize_str(&hex_string)
 }
+
+/// Deserialize a transaction target, an empty string is used for contract creation
+pub fn deserialize_optional_address<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Option<String> = Option::deserialize(deserializer)?;
+    match value {
+        None => Ok(None),
+        Some(value) => {
+            let value = value.trim_start_matches("0x");
+            if value.is_empty() {
+                return Ok(None);
+            }
+            U256::from_str_radix(value, 16).map(Some).map_err(Error::custom)
+        }
+    }
+}
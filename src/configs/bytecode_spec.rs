@@ -58,6 +58,8 @@ pub mod opcodes {
         pub const CHAINID: u8 = 0x46u8;
         pub const SELFBALANCE: u8 = 0x47u8;
         pub const BASEFEE: u8 = 0x48u8;
+        pub const BLOBHASH: u8 = 0x49u8;
+        pub const BLOBBASEFEE: u8 = 0x4Au8;
 
         pub const POP: u8 = 0x50u8;
         pub const MLOAD: u8 = 0x51u8;
@@ -71,7 +73,11 @@ pub mod opcodes {
         pub const MSIZE: u8 = 0x59u8;
         pub const GAS: u8 = 0x5Au8;
         pub const JUMPDEST: u8 = 0x5Bu8;
+        pub const TLOAD: u8 = 0x5Cu8;
+        pub const TSTORE: u8 = 0x5Du8;
+        pub const MCOPY: u8 = 0x5Eu8;
 
+        pub const PUSH_0: u8 = 0x5Fu8;
         pub const PUSH_1: u8 = 0x60u8;
         pub const PUSH_2: u8 = 0x61u8;
         pub const PUSH_3: u8 = 0x62u8;
@@ -149,6 +155,17 @@ pub mod opcodes {
         pub const DUP: u8 = 0xB1u8;
         pub const SWAP: u8 = 0xB2u8;
 
+        // EOF only (EIP-7480, EIP-4200, EIP-4750)
+        pub const DATALOAD: u8 = 0xD0u8;
+        pub const DATALOADN: u8 = 0xD1u8;
+        pub const DATASIZE: u8 = 0xD2u8;
+        pub const DATACOPY: u8 = 0xD3u8;
+        pub const RJUMP: u8 = 0xE0u8;
+        pub const RJUMPI: u8 = 0xE1u8;
+        pub const RJUMPV: u8 = 0xE2u8;
+        pub const CALLF: u8 = 0xE3u8;
+        pub const RETF: u8 = 0xE4u8;
+
         pub const CREATE: u8 = 0xF0u8;
         pub const CALL: u8 = 0xF1u8;
         pub const CALLCODE: u8 = 0xF2u8;
@@ -156,10 +173,68 @@ pub mod opcodes {
         pub const DELEGATECALL: u8 = 0xF4u8;
         pub const CREATE2: u8 = 0xF5u8;
 
+        // EOF only (EIP-7069)
+        pub const EXTCALL: u8 = 0xF8u8;
         pub const STATICCALL: u8 = 0xFAu8;
 
         pub const REVERT: u8 = 0xFDu8;
+        pub const INVALID: u8 = 0xFEu8;
 
         pub const SELFDESTRUCT: u8 = 0xFFu8;
     }
 }
+
+use crate::configs::hardfork::Hardfork;
+
+/// Maximum size of the init code of a contract creation (EIP-3860)
+pub const MAX_INITCODE_SIZE: usize = 49152;
+
+/// Maximum size of deployed contract code (EIP-170)
+pub const MAX_CODE_SIZE: usize = 24576;
+
+/// Deployed code may not start with this byte, it is reserved for EOF (EIP-3541)
+pub const RESERVED_CODE_PREFIX: u8 = 0xEF;
+
+/// Returns false for opcodes that had not been introduced yet in the given hardfork
+#[inline]
+pub fn is_enabled(opcode: u8, hardfork: Hardfork) -> bool {
+    match opcode {
+        opcodes::DELEGATECALL => hardfork.is_enabled(Hardfork::Homestead),
+        | opcodes::REVERT
+        | opcodes::RETURNDATASIZE
+        | opcodes::RETURNDATACOPY
+        | opcodes::STATICCALL => hardfork.is_enabled(Hardfork::Byzantium),
+        | opcodes::SHL
+        | opcodes::SHR
+        | opcodes::SAR
+        | opcodes::CREATE2
+        | opcodes::EXTCODEHASH => hardfork.is_enabled(Hardfork::Constantinople),
+        opcodes::CHAINID | opcodes::SELFBALANCE => hardfork.is_enabled(Hardfork::Istanbul),
+        opcodes::BASEFEE => hardfork.is_enabled(Hardfork::London),
+        opcodes::PUSH_0 => hardfork.is_enabled(Hardfork::Shanghai),
+        | opcodes::TLOAD
+        | opcodes::TSTORE
+        | opcodes::MCOPY
+        | opcodes::BLOBHASH
+        | opcodes::BLOBBASEFEE => hardfork.is_enabled(Hardfork::Cancun),
+        _ => true,
+    }
+}
+
+/// Returns true for opcodes that only exist in EOF code
+#[inline]
+pub fn is_eof_only(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        | opcodes::DATALOAD
+        | opcodes::DATALOADN
+        | opcodes::DATASIZE
+        | opcodes::DATACOPY
+        | opcodes::RJUMP
+        | opcodes::RJUMPI
+        | opcodes::RJUMPV
+        | opcodes::CALLF
+        | opcodes::RETF
+        | opcodes::EXTCALL
+    )
+}
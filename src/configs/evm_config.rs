@@ -0,0 +1,32 @@
+use crate::configs::hardfork::Hardfork;
+use crate::evm_logic::evm::precompiles::PrecompileRegistry;
+
+/// Rules the EVM executes with, chosen by the host
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EVMConfig {
+    pub hardfork: Hardfork,
+    /// Enables the EVM Object Format (EIP-3540, EIP-3670, EIP-4200, EIP-4750, EIP-5450),
+    /// which isn't part of any hardfork yet
+    pub eof: bool,
+    /// Enables the P256VERIFY precompile at 0x100 (RIP-7212), used by L2 chains
+    pub p256_verify: bool,
+    /// Precompiles added, replaced or removed by the host
+    pub precompiles: PrecompileRegistry,
+}
+
+impl EVMConfig {
+    pub fn new(hardfork: Hardfork) -> EVMConfig {
+        EVMConfig {
+            hardfork: hardfork,
+            eof: false,
+            p256_verify: false,
+            precompiles: PrecompileRegistry::new(),
+        }
+    }
+}
+
+impl From<Hardfork> for EVMConfig {
+    fn from(hardfork: Hardfork) -> Self {
+        EVMConfig::new(hardfork)
+    }
+}
@@ -4,6 +4,7 @@ use precompile_costs::G_SHA256;
 use primitive_types::{ H256, U256 };
 use static_costs::{ G_COLD_ACCOUNT_ACCESS, G_NEW_ACCOUNT, G_SELF_DESTRUCT };
 
+use crate::configs::hardfork::Hardfork;
 use crate::evm_logic::util::{ ZERO, ZERO_H256 };
 
 pub mod static_costs {
@@ -12,6 +13,8 @@ pub mod static_costs {
     pub const G_BASE: u64 = 2;
     pub const G_VERY_LOW: u64 = 3;
     pub const G_LOW: u64 = 5;
+    pub const G_RJUMPI: u64 = 4;
+    pub const G_DATALOAD: u64 = 4;
     pub const G_MID: u64 = 8;
     pub const G_HIGH: u64 = 10;
     pub const G_WARM_ACCESS: u64 = 100;
@@ -22,16 +25,26 @@ pub mod static_costs {
     pub const G_SSET: u64 = 20000;
     pub const G_SRESET: u64 = 2900;
     pub const R_SCLEAR: u64 = 15000;
+    pub const R_SCLEAR_LONDON: u64 = 4800;
+    pub const G_SSTORE_SENTRY: u64 = 2300;
+    pub const MAX_REFUND_QUOTIENT: u64 = 2;
+    pub const MAX_REFUND_QUOTIENT_LONDON: u64 = 5;
     pub const G_SELF_DESTRUCT: u64 = 5000;
+    pub const R_SELF_DESTRUCT: u64 = 24000;
     pub const G_CREATE: u64 = 32000;
     pub const G_CODE_DEPOSIT: u64 = 200;
     pub const G_CALL_VALUE: u64 = 9000;
     pub const G_CALL_STIPEND: u64 = 2300;
+    pub const G_EXTCALL_MIN_RETAINED: u64 = 5000;
+    pub const G_EXTCALL_MIN_CALLEE: u64 = 2300;
     pub const G_NEW_ACCOUNT: u64 = 25000;
     pub const G_EXP: u64 = 10;
     pub const G_EXP_BYTE: u64 = 50;
     pub const G_MEMORY: u64 = 3;
     pub const G_TX_CREATE: u64 = 32000;
+    pub const G_INITCODE_WORD: u64 = 2;
+    pub const G_AUTHORIZATION: u64 = 25000;
+    pub const G_AUTHORIZATION_BASE: u64 = 12500;
     pub const G_TX_DATA_ZERO: u64 = 4;
     pub const G_TX_DATA_NON_ZERO: u64 = 16;
     pub const G_TRANSACTION: u64 = 21000;
@@ -42,11 +55,81 @@ pub mod static_costs {
     pub const G_KECCAK256_WORD: u64 = 6;
     pub const G_COPY: u64 = 3;
     pub const G_BLOCK_HASH: u64 = 20;
+
+    // Costs replaced by later hardforks, named after the fork that introduced them
+    pub const G_SLOAD_FRONTIER: u64 = 50;
+    pub const G_SLOAD_TANGERINE_WHISTLE: u64 = 200;
+    pub const G_SLOAD_ISTANBUL: u64 = 800;
+    pub const G_BALANCE_FRONTIER: u64 = 20;
+    pub const G_BALANCE_TANGERINE_WHISTLE: u64 = 400;
+    pub const G_BALANCE_ISTANBUL: u64 = 700;
+    pub const G_EXTCODE_FRONTIER: u64 = 20;
+    pub const G_EXTCODE_TANGERINE_WHISTLE: u64 = 700;
+    pub const G_EXTCODEHASH_CONSTANTINOPLE: u64 = 400;
+    pub const G_EXTCODEHASH_ISTANBUL: u64 = 700;
+    pub const G_CALL_FRONTIER: u64 = 40;
+    pub const G_CALL_TANGERINE_WHISTLE: u64 = 700;
+    pub const G_SRESET_FRONTIER: u64 = 5000;
+    pub const G_EXP_BYTE_FRONTIER: u64 = 10;
+    pub const G_TX_DATA_NON_ZERO_FRONTIER: u64 = 68;
+}
+
+pub mod blob_costs {
+    pub const MIN_BLOB_BASE_FEE: u64 = 1;
+    pub const BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN: u64 = 3338477;
+    pub const BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE: u64 = 5007716;
 }
 
 pub mod precompile_costs {
     pub const G_ECRECOVER: u64 = 3000;
     pub const G_SHA256: u64 = 60;
+    pub const G_RIPEMD160: u64 = 600;
+    pub const G_RIPEMD160_WORD: u64 = 120;
+    pub const G_IDENTITY: u64 = 15;
+    pub const G_IDENTITY_WORD: u64 = 3;
+    pub const G_MODEXP_QUAD_DIVISOR: u64 = 20;
+    pub const G_MODEXP_MIN: u64 = 200;
+    pub const G_MODEXP_DIVISOR: u64 = 3;
+    pub const G_ECADD_BYZANTIUM: u64 = 500;
+    pub const G_ECADD: u64 = 150;
+    pub const G_ECMUL_BYZANTIUM: u64 = 40000;
+    pub const G_ECMUL: u64 = 6000;
+    pub const G_ECPAIRING_BYZANTIUM: u64 = 100000;
+    pub const G_ECPAIRING_PAIR_BYZANTIUM: u64 = 80000;
+    pub const G_ECPAIRING: u64 = 45000;
+    pub const G_ECPAIRING_PAIR: u64 = 34000;
+    pub const G_P256VERIFY: u64 = 3450;
+    pub const G_BLS12_G1ADD: u64 = 375;
+    pub const G_BLS12_G1MSM: u64 = 12000;
+    pub const G_BLS12_G2ADD: u64 = 600;
+    pub const G_BLS12_G2MSM: u64 = 22500;
+    pub const G_BLS12_PAIRING: u64 = 37700;
+    pub const G_BLS12_PAIRING_PAIR: u64 = 32600;
+    pub const G_BLS12_MAP_FP_TO_G1: u64 = 5500;
+    pub const G_BLS12_MAP_FP2_TO_G2: u64 = 23800;
+    pub const BLS12_MSM_MULTIPLIER: u64 = 1000;
+    /// Per mille discount for a G1 MSM of k points, the last entry is used for larger k (EIP-2537)
+    pub const BLS12_G1MSM_DISCOUNTS: [u64; 128] = [
+        1000, 949, 848, 797, 764, 750, 738, 728, 719, 712, 705, 698, 692, 687, 682, 677,
+        673, 669, 665, 661, 658, 654, 651, 648, 645, 642, 640, 637, 635, 632, 630, 627,
+        625, 623, 621, 619, 617, 615, 613, 611, 609, 608, 606, 604, 603, 601, 599, 598,
+        596, 595, 593, 592, 591, 589, 588, 586, 585, 584, 582, 581, 580, 579, 577, 576,
+        575, 574, 573, 572, 570, 569, 568, 567, 566, 565, 564, 563, 562, 561, 560, 559,
+        558, 557, 556, 555, 554, 553, 552, 551, 550, 549, 548, 547, 547, 546, 545, 544,
+        543, 542, 541, 540, 540, 539, 538, 537, 536, 536, 535, 534, 533, 532, 532, 531,
+        530, 529, 528, 528, 527, 526, 525, 525, 524, 523, 522, 522, 521, 520, 520, 519,
+    ];
+    /// Per mille discount for a G2 MSM of k points, the last entry is used for larger k (EIP-2537)
+    pub const BLS12_G2MSM_DISCOUNTS: [u64; 128] = [
+        1000, 1000, 923, 884, 855, 832, 812, 796, 782, 770, 759, 749, 740, 732, 724, 717,
+        711, 704, 699, 693, 688, 683, 679, 674, 670, 666, 663, 659, 655, 652, 649, 646,
+        643, 640, 637, 634, 632, 629, 627, 624, 622, 620, 618, 615, 613, 611, 609, 607,
+        606, 604, 602, 600, 598, 597, 595, 593, 592, 590, 589, 587, 586, 584, 583, 582,
+        580, 579, 578, 576, 575, 574, 573, 571, 570, 569, 568, 567, 566, 565, 563, 562,
+        561, 560, 559, 558, 557, 556, 555, 554, 553, 552, 552, 551, 550, 549, 548, 547,
+        546, 545, 545, 544, 543, 542, 541, 541, 540, 539, 538, 537, 537, 536, 535, 535,
+        534, 533, 532, 532, 531, 530, 530, 529, 528, 528, 527, 526, 526, 525, 524, 524,
+    ];
 }
 
 pub enum DynamicCosts {
@@ -59,6 +142,10 @@ pub enum DynamicCosts {
     ExtCodeHash {
         target_is_cold: bool,
     },
+    /// Gas cost for loading the code of the account a called account delegates to (EIP-7702)
+    DelegationAccess {
+        target_is_cold: bool,
+    },
     Call {
         value: U256,
         target_is_cold: bool,
@@ -66,6 +153,12 @@ pub enum DynamicCosts {
         is_delegate: bool,
         is_code: bool,
     },
+    /// Gas cost for `EXTCALL` (EIP-7069), which has no call stipend
+    ExtCall {
+        value: U256,
+        target_is_cold: bool,
+        empty_account: bool,
+    },
     StaticCall {
         /// Call gas.
         gas: U256,
@@ -91,12 +184,18 @@ pub enum DynamicCosts {
         /// Power of `EXP`.
         power: U256,
     },
+    Create,
     Create2 {
-        /// Length.
-        len: U256,
+        /// Length of the init code, which is hashed to find the address
+        len: usize,
     },
-    Create {
-        deployed_code_size: usize,
+    /// Gas cost for storing the code returned by init code
+    CodeDeposit {
+        size: usize,
+    },
+    /// Gas cost for metering init code (EIP-3860)
+    InitCode {
+        size: usize,
     },
     /// Gas cost for `SLOAD`.
     SLoad {
@@ -120,31 +219,46 @@ pub enum DynamicCosts {
         address_exists: bool,
         is_cold: bool,
         positive_balance: bool,
+        /// True if the contract has already self destructed in this transaction
+        already_deleted: bool,
     },
 }
 
 impl DynamicCosts {
     // TODO add error here
     #[inline]
-    pub fn cost(&self) -> u64 {
+    pub fn cost(&self, hardfork: Hardfork) -> u64 {
         match self {
             DynamicCosts::Balance { target_is_cold } => {
-                if *target_is_cold {
-                    static_costs::G_COLDS_LOAD
+                if !hardfork.is_enabled(Hardfork::Berlin) {
+                    balance_cost(hardfork)
+                } else if *target_is_cold {
+                    static_costs::G_COLD_ACCOUNT_ACCESS
                 } else {
                     static_costs::G_WARM_ACCESS
                 }
             }
             DynamicCosts::ExtCodeSize { target_is_cold } => {
-                if *target_is_cold {
-                    static_costs::G_COLDS_LOAD
+                if !hardfork.is_enabled(Hardfork::Berlin) {
+                    ext_code_cost(hardfork)
+                } else if *target_is_cold {
+                    static_costs::G_COLD_ACCOUNT_ACCESS
                 } else {
                     static_costs::G_WARM_ACCESS
                 }
             }
             DynamicCosts::ExtCodeHash { target_is_cold } => {
+                if !hardfork.is_enabled(Hardfork::Berlin) {
+                    ext_code_hash_cost(hardfork)
+                } else if *target_is_cold {
+                    static_costs::G_COLD_ACCOUNT_ACCESS
+                } else {
+                    static_costs::G_WARM_ACCESS
+                }
+            }
+            DynamicCosts::DelegationAccess { target_is_cold } => {
                 if *target_is_cold {
-                    static_costs::G_COLDS_LOAD
+                    static_costs::G_COLD_ACCOUNT_ACCESS
                 } else {
                     static_costs::G_WARM_ACCESS
                 }
@@ -152,8 +266,13 @@ impl DynamicCosts {
             DynamicCosts::Call { value, empty_account, target_is_cold, is_delegate, is_code } => {
                 // println!("empty_account: {}", empty_account);
                 println!("target_is_cold {}", target_is_cold);
+                // Before EIP-161 calling an account that doesn't exist costs the same without value
+                let creates_account =
+                    !value.eq(&ZERO) || !hardfork.is_enabled(Hardfork::SpuriousDragon);
                 0 +
-                    (if *target_is_cold {
+                    (if !hardfork.is_enabled(Hardfork::Berlin) {
+                        call_cost(hardfork)
+                    } else if *target_is_cold {
                         static_costs::G_COLD_ACCOUNT_ACCESS
                     } else {
                         static_costs::G_WARM_ACCESS
@@ -163,12 +282,21 @@ impl DynamicCosts {
                     } else {
                         0
                     }) +
-                    (if !value.eq(&ZERO) & *empty_account & !is_delegate & !is_code {
+                    (if creates_account & *empty_account & !is_delegate & !is_code {
                         static_costs::G_NEW_ACCOUNT
                     } else {
                         0
                     })
             }
+            DynamicCosts::ExtCall { value, target_is_cold, empty_account } => {
+                (if *target_is_cold {
+                    static_costs::G_COLD_ACCOUNT_ACCESS
+                } else {
+                    static_costs::G_WARM_ACCESS
+                }) +
+                    (if value.is_zero() { 0 } else { static_costs::G_CALL_VALUE }) +
+                    (if !value.is_zero() && *empty_account { static_costs::G_NEW_ACCOUNT } else { 0 })
+            }
             DynamicCosts::Keccak256 { len } => {
                 // println!("Len in Keccak256: {}", len);
                 let x =
@@ -185,7 +313,12 @@ impl DynamicCosts {
             DynamicCosts::Exp { power } => {
                 // bytes_for_u256
                 let bytes = (power.bits() + 7) / 8;
-                static_costs::G_EXP + static_costs::G_EXP_BYTE * (bytes as u64)
+                let byte_cost = if hardfork.is_enabled(Hardfork::SpuriousDragon) {
+                    static_costs::G_EXP_BYTE
+                } else {
+                    static_costs::G_EXP_BYTE_FRONTIER
+                };
+                static_costs::G_EXP + byte_cost * (bytes as u64)
             }
 
             DynamicCosts::Copy { size_bytes } => {
@@ -198,76 +331,574 @@ impl DynamicCosts {
                 // println!("size_bytes: {}", size_bytes);
                 // println!("size_bytes.div_ceil(32): {}", size_bytes.div_ceil(32) as u64);
                 static_costs::G_COPY * (size_bytes.div_ceil(32) as u64) +
-                    (if *target_is_cold {
+                    (if !hardfork.is_enabled(Hardfork::Berlin) {
+                        ext_code_cost(hardfork)
+                    } else if *target_is_cold {
                         static_costs::G_COLD_ACCOUNT_ACCESS
                     } else {
                         static_costs::G_WARM_ACCESS
                     })
             }
-            DynamicCosts::Create { deployed_code_size } => {
-                // println!("Code Size {}",deployed_code_size);
+            DynamicCosts::Create => static_costs::G_CREATE,
+            DynamicCosts::Create2 { len } => {
                 static_costs::G_CREATE +
-                    static_costs::G_KECCAK256_WORD * (*deployed_code_size as u64).div_ceil(32)
+                    static_costs::G_KECCAK256_WORD * (*len as u64).div_ceil(32)
+            }
+            DynamicCosts::CodeDeposit { size } => static_costs::G_CODE_DEPOSIT * (*size as u64),
+            DynamicCosts::InitCode { size } => {
+                static_costs::G_INITCODE_WORD * (*size as u64).div_ceil(32)
             }
             DynamicCosts::SLoad { target_is_cold } => {
                 // println!("Is cold: {}", target_is_cold);
-                if *target_is_cold {
+                if !hardfork.is_enabled(Hardfork::Berlin) {
+                    sload_cost(hardfork)
+                } else if *target_is_cold {
                     static_costs::G_COLDS_LOAD
                 } else {
                     static_costs::G_WARM_ACCESS
                 }
             }
             DynamicCosts::SStore { original, current, new, target_is_cold } => {
-                let mut gas_cost = if *target_is_cold { static_costs::G_COLDS_LOAD } else { 0 };
+                if !net_gas_metering(hardfork) {
+                    return if current.eq(&ZERO_H256) && !new.eq(&ZERO_H256) {
+                        static_costs::G_SSET
+                    } else {
+                        static_costs::G_SRESET_FRONTIER
+                    };
+                }
+                let mut gas_cost = if *target_is_cold && hardfork.is_enabled(Hardfork::Berlin) {
+                    static_costs::G_COLDS_LOAD
+                } else {
+                    0
+                };
                 gas_cost += if current.eq(&new) | !original.eq(&current) {
                     // println!("Warm access");
-                    static_costs::G_WARM_ACCESS
+                    warm_sload_cost(hardfork)
                 } else if original.eq(&ZERO_H256) {
                     static_costs::G_SSET
                 } else {
-                    static_costs::G_SRESET
+                    sreset_cost(hardfork)
                 };
                 gas_cost
             }
-            DynamicCosts::SelfDestruct { address_exists, is_cold, positive_balance } => {
+            DynamicCosts::SelfDestruct { address_exists, is_cold, positive_balance, .. } => {
+                // Free until EIP-150, which also charged for creating the beneficiary regardless
+                // of the balance until EIP-161
+                if !hardfork.is_enabled(Hardfork::TangerineWhistle) {
+                    return 0;
+                }
+                let creates_account =
+                    *positive_balance || !hardfork.is_enabled(Hardfork::SpuriousDragon);
                 G_SELF_DESTRUCT +
                     (if !*address_exists {
-                        if *positive_balance { G_NEW_ACCOUNT } else { 0 }
+                        if creates_account { G_NEW_ACCOUNT } else { 0 }
                     } else {
                         0
                     }) +
-                    (if *is_cold { G_COLD_ACCOUNT_ACCESS } else { 0 })
+                    (if *is_cold && hardfork.is_enabled(Hardfork::Berlin) {
+                        G_COLD_ACCOUNT_ACCESS
+                    } else {
+                        0
+                    })
             }
             _ => 0,
         }
     }
-    pub fn refund(&self) -> u64 {
+    pub fn refund(&self, hardfork: Hardfork) -> i64 {
         match self {
-            DynamicCosts::SStore { original, current, new, target_is_cold } => {
-                if !original.eq(&ZERO_H256) && new.eq(&ZERO_H256) {
-                    static_costs::R_SCLEAR
+            DynamicCosts::SStore { original, current, new, .. } => {
+                if !net_gas_metering(hardfork) {
+                    return if !current.eq(&ZERO_H256) && new.eq(&ZERO_H256) {
+                        static_costs::R_SCLEAR as i64
+                    } else {
+                        0
+                    };
+                }
+                // EIP-1283 and EIP-2200 refund rules with the costs of the current fork
+                let r_sclear = (if hardfork.is_enabled(Hardfork::London) {
+                    static_costs::R_SCLEAR_LONDON
                 } else {
+                    static_costs::R_SCLEAR
+                }) as i64;
+                let mut refund = 0;
+                if current.eq(new) {
+                    return refund;
+                }
+                if original.eq(current) {
+                    if !original.eq(&ZERO_H256) && new.eq(&ZERO_H256) {
+                        refund += r_sclear;
+                    }
+                    return refund;
+                }
+                if !original.eq(&ZERO_H256) {
+                    if current.eq(&ZERO_H256) {
+                        // Undo the refund given when the slot was cleared
+                        refund -= r_sclear;
+                    } else if new.eq(&ZERO_H256) {
+                        refund += r_sclear;
+                    }
+                }
+                if original.eq(new) {
+                    // Slot restored to its original value, refund all but the warm access cost
+                    refund += (if original.eq(&ZERO_H256) {
+                        static_costs::G_SSET - warm_sload_cost(hardfork)
+                    } else {
+                        sreset_cost(hardfork) - warm_sload_cost(hardfork)
+                    }) as i64;
+                }
+                refund
+            }
+            DynamicCosts::SelfDestruct { already_deleted, .. } => {
+                // Removed in London (EIP-3529)
+                if hardfork.is_enabled(Hardfork::London) || *already_deleted {
                     0
+                } else {
+                    static_costs::R_SELF_DESTRUCT as i64
                 }
             }
-            DynamicCosts::SelfDestruct { .. } => { 24000 }
             _ => 0,
         }
     }
 }
 
+/// SLOAD cost before access lists (EIP-2929)
+fn sload_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Istanbul) {
+        static_costs::G_SLOAD_ISTANBUL
+    } else if hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        static_costs::G_SLOAD_TANGERINE_WHISTLE
+    } else {
+        static_costs::G_SLOAD_FRONTIER
+    }
+}
+
+/// Cost of reading a slot that has already been accessed, charged by SSTORE for dirty slots
+fn warm_sload_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Berlin) {
+        static_costs::G_WARM_ACCESS
+    } else {
+        sload_cost(hardfork)
+    }
+}
+
+/// SSTORE cost for changing a non zero slot, which includes the cold read from Berlin
+fn sreset_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Berlin) {
+        static_costs::G_SRESET
+    } else {
+        static_costs::G_SRESET_FRONTIER
+    }
+}
+
+/// Net gas metering for SSTORE, added in Constantinople (EIP-1283), removed again in Petersburg
+/// and reintroduced in Istanbul (EIP-2200)
+fn net_gas_metering(hardfork: Hardfork) -> bool {
+    hardfork == Hardfork::Constantinople || hardfork.is_enabled(Hardfork::Istanbul)
+}
+
+fn balance_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Istanbul) {
+        static_costs::G_BALANCE_ISTANBUL
+    } else if hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        static_costs::G_BALANCE_TANGERINE_WHISTLE
+    } else {
+        static_costs::G_BALANCE_FRONTIER
+    }
+}
+
+/// EXTCODESIZE and base EXTCODECOPY cost before access lists
+fn ext_code_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        static_costs::G_EXTCODE_TANGERINE_WHISTLE
+    } else {
+        static_costs::G_EXTCODE_FRONTIER
+    }
+}
+
+fn ext_code_hash_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Istanbul) {
+        static_costs::G_EXTCODEHASH_ISTANBUL
+    } else {
+        static_costs::G_EXTCODEHASH_CONSTANTINOPLE
+    }
+}
+
+/// Base cost of the CALL family before access lists
+fn call_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        static_costs::G_CALL_TANGERINE_WHISTLE
+    } else {
+        static_costs::G_CALL_FRONTIER
+    }
+}
+
+/// Intrinsic cost of a non zero byte of transaction data, reduced by EIP-2028
+pub fn tx_data_non_zero_cost(hardfork: Hardfork) -> u64 {
+    if hardfork.is_enabled(Hardfork::Istanbul) {
+        static_costs::G_TX_DATA_NON_ZERO
+    } else {
+        static_costs::G_TX_DATA_NON_ZERO_FRONTIER
+    }
+}
+
 pub enum DynamicPreCompileCosts {
     Sha256 {
         data_word_size: usize,
     },
+    Ripemd160 {
+        data_word_size: usize,
+    },
+    Identity {
+        data_word_size: usize,
+    },
+    ModExp {
+        base_length: U256,
+        exponent_length: U256,
+        modulus_length: U256,
+        /// The first 32 bytes of the exponent
+        exponent_head: U256,
+    },
+    EcAdd,
+    EcMul,
+    EcPairing {
+        pairs: usize,
+    },
+    Bls12G1Msm {
+        pairs: usize,
+    },
+    Bls12G2Msm {
+        pairs: usize,
+    },
+    Bls12Pairing {
+        pairs: usize,
+    },
 }
 
 impl DynamicPreCompileCosts {
-    pub fn cost(&self) -> u64 {
+    pub fn cost(&self, hardfork: Hardfork) -> u64 {
         match self {
             DynamicPreCompileCosts::Sha256 { data_word_size } => {
                 G_SHA256 + 12 * (*data_word_size as u64)
             }
+            DynamicPreCompileCosts::Ripemd160 { data_word_size } => {
+                precompile_costs::G_RIPEMD160 +
+                    precompile_costs::G_RIPEMD160_WORD * (*data_word_size as u64)
+            }
+            DynamicPreCompileCosts::Identity { data_word_size } => {
+                precompile_costs::G_IDENTITY +
+                    precompile_costs::G_IDENTITY_WORD * (*data_word_size as u64)
+            }
+            DynamicPreCompileCosts::ModExp {
+                base_length,
+                exponent_length,
+                modulus_length,
+                exponent_head,
+            } => {
+                let max_length = *base_length.max(modulus_length);
+                let iterations = modexp_iterations(*exponent_length, *exponent_head).max(
+                    U256::one()
+                );
+                let cost = if hardfork.is_enabled(Hardfork::Berlin) {
+                    // EIP-2565
+                    let words = max_length.saturating_add(U256::from(7)) / 8;
+                    (words.saturating_mul(words).saturating_mul(iterations) /
+                        precompile_costs::G_MODEXP_DIVISOR).max(
+                        U256::from(precompile_costs::G_MODEXP_MIN)
+                    )
+                } else {
+                    // EIP-198
+                    modexp_complexity_frontier(max_length).saturating_mul(iterations) /
+                        precompile_costs::G_MODEXP_QUAD_DIVISOR
+                };
+                if cost > U256::from(u64::MAX) { u64::MAX } else { cost.as_u64() }
+            }
+            DynamicPreCompileCosts::EcAdd => {
+                // EIP-1108
+                if hardfork.is_enabled(Hardfork::Istanbul) {
+                    precompile_costs::G_ECADD
+                } else {
+                    precompile_costs::G_ECADD_BYZANTIUM
+                }
+            }
+            DynamicPreCompileCosts::EcMul => {
+                if hardfork.is_enabled(Hardfork::Istanbul) {
+                    precompile_costs::G_ECMUL
+                } else {
+                    precompile_costs::G_ECMUL_BYZANTIUM
+                }
+            }
+            DynamicPreCompileCosts::EcPairing { pairs } => {
+                if hardfork.is_enabled(Hardfork::Istanbul) {
+                    precompile_costs::G_ECPAIRING +
+                        precompile_costs::G_ECPAIRING_PAIR * (*pairs as u64)
+                } else {
+                    precompile_costs::G_ECPAIRING_BYZANTIUM +
+                        precompile_costs::G_ECPAIRING_PAIR_BYZANTIUM * (*pairs as u64)
+                }
+            }
+            DynamicPreCompileCosts::Bls12G1Msm { pairs } => {
+                msm_cost(
+                    *pairs,
+                    precompile_costs::G_BLS12_G1MSM,
+                    &precompile_costs::BLS12_G1MSM_DISCOUNTS
+                )
+            }
+            DynamicPreCompileCosts::Bls12G2Msm { pairs } => {
+                msm_cost(
+                    *pairs,
+                    precompile_costs::G_BLS12_G2MSM,
+                    &precompile_costs::BLS12_G2MSM_DISCOUNTS
+                )
+            }
+            DynamicPreCompileCosts::Bls12Pairing { pairs } => {
+                precompile_costs::G_BLS12_PAIRING +
+                    precompile_costs::G_BLS12_PAIRING_PAIR * (*pairs as u64)
+            }
         }
     }
 }
+
+/// Number of squarings needed for the exponent, the adjusted exponent length of EIP-198
+fn modexp_iterations(exponent_length: U256, exponent_head: U256) -> U256 {
+    let head_bits = U256::from(exponent_head.bits().saturating_sub(1));
+    if exponent_length <= U256::from(32) {
+        head_bits
+    } else {
+        (exponent_length - 32).saturating_mul(U256::from(8)).saturating_add(head_bits)
+    }
+}
+
+/// Multiplication complexity from EIP-198
+fn modexp_complexity_frontier(length: U256) -> U256 {
+    let square = length.saturating_mul(length);
+    if length <= U256::from(64) {
+        square
+    } else if length <= U256::from(1024) {
+        square / 4 + length * 96 - 3072
+    } else {
+        (square / 16).saturating_add(length.saturating_mul(U256::from(480))) - 199680
+    }
+}
+
+/// Cost of a BLS12-381 multi scalar multiplication, discounted by the number of points
+fn msm_cost(pairs: usize, cost_per_pair: u64, discounts: &[u64]) -> u64 {
+    if pairs == 0 {
+        return 0;
+    }
+    let discount = discounts[pairs.min(discounts.len()) - 1];
+    ((pairs as u64) * cost_per_pair * discount) / precompile_costs::BLS12_MSM_MULTIPLIER
+}
+
+/// Price of a unit of blob gas derived from the block's excess blob gas (EIP-4844, EIP-7691)
+pub fn blob_base_fee(excess_blob_gas: U256, hardfork: Hardfork) -> U256 {
+    let update_fraction = if hardfork.is_enabled(Hardfork::Prague) {
+        blob_costs::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE
+    } else {
+        blob_costs::BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN
+    };
+    fake_exponential(
+        U256::from(blob_costs::MIN_BLOB_BASE_FEE),
+        excess_blob_gas,
+        U256::from(update_fraction)
+    )
+}
+
+/// Approximates factor * e ** (numerator / denominator) using a Taylor expansion
+fn fake_exponential(factor: U256, numerator: U256, denominator: U256) -> U256 {
+    let mut i = U256::one();
+    let mut output = U256::zero();
+    let mut numerator_accum = match factor.checked_mul(denominator) {
+        Some(value) => value,
+        None => {
+            return U256::MAX;
+        }
+    };
+    while !numerator_accum.is_zero() {
+        output = match output.checked_add(numerator_accum) {
+            Some(value) => value,
+            None => {
+                return U256::MAX;
+            }
+        };
+        numerator_accum = match numerator_accum.checked_mul(numerator) {
+            Some(value) => value / (denominator * i),
+            None => {
+                return U256::MAX;
+            }
+        };
+        i += U256::one();
+    }
+    output / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sstore_refund(original: u64, current: u64, new: u64, hardfork: Hardfork) -> i64 {
+        (DynamicCosts::SStore {
+            original: H256::from_low_u64_be(original),
+            current: H256::from_low_u64_be(current),
+            new: H256::from_low_u64_be(new),
+            target_is_cold: false,
+        }).refund(hardfork)
+    }
+
+    #[test]
+    fn test_fake_exponential() {
+        // Vectors from the EIP-4844 reference tests as (factor, numerator, denominator, expected)
+        let vectors: [(u64, u64, u64, u64); 15] = [
+            (1, 0, 1, 1),
+            (38493, 0, 1000, 38493),
+            (0, 1234, 2345, 0),
+            (1, 2, 1, 6),
+            (1, 4, 2, 6),
+            (1, 3, 1, 16),
+            (1, 6, 2, 18),
+            (1, 4, 1, 49),
+            (1, 8, 2, 50),
+            (10, 8, 2, 542),
+            (11, 8, 2, 596),
+            (1, 5, 1, 136),
+            (1, 5, 2, 11),
+            (2, 5, 2, 23),
+            (1, 50000000, 2225652, 5709098764),
+        ];
+        for (factor, numerator, denominator, expected) in vectors {
+            assert_eq!(
+                fake_exponential(U256::from(factor), U256::from(numerator), U256::from(denominator)),
+                U256::from(expected)
+            );
+        }
+        // Saturates instead of overflowing
+        assert_eq!(fake_exponential(U256::MAX, U256::one(), U256::from(2)), U256::MAX);
+    }
+
+    #[test]
+    fn test_blob_base_fee() {
+        assert_eq!(blob_base_fee(U256::zero(), Hardfork::Cancun), U256::one());
+        assert_eq!(blob_base_fee(U256::from(3338477), Hardfork::Cancun), U256::from(2));
+        assert_eq!(blob_base_fee(U256::from(33384770), Hardfork::Cancun), U256::from(22026));
+        // EIP-7691 raises the update fraction
+        assert_eq!(blob_base_fee(U256::from(33384770), Hardfork::Prague), U256::from(785));
+    }
+
+    #[test]
+    fn test_sstore_refunds() {
+        // Clearing a slot
+        assert_eq!(sstore_refund(1, 1, 0, Hardfork::Berlin), 15000);
+        assert_eq!(sstore_refund(1, 1, 0, Hardfork::London), 4800);
+        // Setting a cleared slot again removes the clearing refund
+        assert_eq!(sstore_refund(1, 0, 2, Hardfork::London), -4800);
+        // Restoring the original value
+        assert_eq!(sstore_refund(0, 1, 0, Hardfork::London), 19900);
+        assert_eq!(sstore_refund(1, 2, 1, Hardfork::London), 2800);
+        assert_eq!(sstore_refund(1, 0, 1, Hardfork::London), -4800 + 2800);
+        // No op
+        assert_eq!(sstore_refund(1, 2, 2, Hardfork::London), 0);
+        // Restoring with the EIP-1283 and Istanbul costs
+        assert_eq!(sstore_refund(0, 1, 0, Hardfork::Constantinople), 19800);
+        assert_eq!(sstore_refund(1, 2, 1, Hardfork::Istanbul), 4200);
+        // No net gas metering in Petersburg
+        assert_eq!(sstore_refund(0, 1, 0, Hardfork::Petersburg), 15000);
+        assert_eq!(sstore_refund(1, 0, 1, Hardfork::Petersburg), 0);
+    }
+
+    #[test]
+    fn test_pre_berlin_costs() {
+        let sload = DynamicCosts::SLoad { target_is_cold: true };
+        assert_eq!(sload.cost(Hardfork::Frontier), 50);
+        assert_eq!(sload.cost(Hardfork::TangerineWhistle), 200);
+        assert_eq!(sload.cost(Hardfork::Istanbul), 800);
+        assert_eq!(sload.cost(Hardfork::Berlin), 2100);
+        let sstore = DynamicCosts::SStore {
+            original: H256::from_low_u64_be(1),
+            current: H256::from_low_u64_be(2),
+            new: H256::from_low_u64_be(3),
+            target_is_cold: true,
+        };
+        assert_eq!(sstore.cost(Hardfork::Petersburg), 5000);
+        assert_eq!(sstore.cost(Hardfork::Constantinople), 200);
+        assert_eq!(sstore.cost(Hardfork::Istanbul), 800);
+        assert_eq!(sstore.cost(Hardfork::Berlin), 2200);
+        let call = DynamicCosts::Call {
+            value: U256::zero(),
+            target_is_cold: true,
+            empty_account: true,
+            is_delegate: false,
+            is_code: false,
+        };
+        assert_eq!(call.cost(Hardfork::Frontier), 40 + 25000);
+        assert_eq!(call.cost(Hardfork::TangerineWhistle), 700 + 25000);
+        assert_eq!(call.cost(Hardfork::SpuriousDragon), 700);
+        let exp = DynamicCosts::Exp { power: U256::from(0x100) };
+        assert_eq!(exp.cost(Hardfork::Homestead), 10 + 2 * 10);
+        assert_eq!(exp.cost(Hardfork::SpuriousDragon), 10 + 2 * 50);
+        let self_destruct = DynamicCosts::SelfDestruct {
+            address_exists: false,
+            is_cold: true,
+            positive_balance: false,
+            already_deleted: false,
+        };
+        assert_eq!(self_destruct.cost(Hardfork::Homestead), 0);
+        assert_eq!(self_destruct.cost(Hardfork::TangerineWhistle), 5000 + 25000);
+        assert_eq!(self_destruct.cost(Hardfork::SpuriousDragon), 5000);
+    }
+
+    fn modexp_cost(lengths: (u64, u64, u64), exponent_head: u64, hardfork: Hardfork) -> u64 {
+        (DynamicPreCompileCosts::ModExp {
+            base_length: U256::from(lengths.0),
+            exponent_length: U256::from(lengths.1),
+            modulus_length: U256::from(lengths.2),
+            exponent_head: U256::from(exponent_head),
+        }).cost(hardfork)
+    }
+
+    #[test]
+    fn test_modexp_costs() {
+        assert_eq!(modexp_cost((1, 1, 1), 3, Hardfork::Byzantium), 0);
+        assert_eq!(modexp_cost((1, 1, 1), 3, Hardfork::Berlin), 200);
+        assert_eq!(modexp_cost((64, 32, 64), 1 << 20, Hardfork::Byzantium), 4096 * 20 / 20);
+        assert_eq!(modexp_cost((256, 32, 256), 1 << 20, Hardfork::Berlin), 1024 * 20 / 3);
+        assert_eq!(
+            modexp_cost((100, 40, 100), 0, Hardfork::Byzantium),
+            ((2500 + 9600 - 3072) * 64) / 20
+        );
+        // Lengths that don't fit in a usize saturate
+        assert_eq!(
+            (DynamicPreCompileCosts::ModExp {
+                base_length: U256::MAX,
+                exponent_length: U256::one(),
+                modulus_length: U256::one(),
+                exponent_head: U256::one(),
+            }).cost(Hardfork::Berlin),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_alt_bn128_costs() {
+        assert_eq!(DynamicPreCompileCosts::EcAdd.cost(Hardfork::Byzantium), 500);
+        assert_eq!(DynamicPreCompileCosts::EcAdd.cost(Hardfork::Istanbul), 150);
+        assert_eq!(DynamicPreCompileCosts::EcMul.cost(Hardfork::Byzantium), 40000);
+        assert_eq!(DynamicPreCompileCosts::EcMul.cost(Hardfork::Istanbul), 6000);
+        assert_eq!(
+            (DynamicPreCompileCosts::EcPairing { pairs: 2 }).cost(Hardfork::Byzantium),
+            100000 + 2 * 80000
+        );
+        assert_eq!(
+            (DynamicPreCompileCosts::EcPairing { pairs: 2 }).cost(Hardfork::Istanbul),
+            45000 + 2 * 34000
+        );
+    }
+
+    #[test]
+    fn test_bls12_msm_costs() {
+        assert_eq!((DynamicPreCompileCosts::Bls12G1Msm { pairs: 1 }).cost(Hardfork::Prague), 12000);
+        assert_eq!((DynamicPreCompileCosts::Bls12G1Msm { pairs: 2 }).cost(Hardfork::Prague), 22776);
+        assert_eq!((DynamicPreCompileCosts::Bls12G2Msm { pairs: 2 }).cost(Hardfork::Prague), 45000);
+        // The last discount applies to any larger MSM
+        assert_eq!((DynamicPreCompileCosts::Bls12G1Msm { pairs: 200 }).cost(Hardfork::Prague), 200 * 12 * 519);
+        assert_eq!((DynamicPreCompileCosts::Bls12Pairing { pairs: 2 }).cost(Hardfork::Prague), 37700 + 2 * 32600);
+    }
+}
@@ -0,0 +1,57 @@
+use std::str::FromStr;
+
+/// Ethereum protocol upgrades, ordered by activation so forks can be compared with `>=`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Hardfork {
+    Frontier,
+    Homestead,
+    /// EIP-150
+    TangerineWhistle,
+    /// EIP-155, EIP-158
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    /// Constantinople without EIP-1283
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    /// The Merge
+    Paris,
+    Shanghai,
+    Cancun,
+    #[default]
+    Prague,
+}
+
+impl Hardfork {
+    #[inline]
+    pub fn is_enabled(&self, fork: Hardfork) -> bool {
+        *self >= fork
+    }
+}
+
+impl FromStr for Hardfork {
+    type Err = ();
+
+    /// Parses both the upgrade names and the fork names used by the official ethereum tests
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "Frontier" => Ok(Hardfork::Frontier),
+            "Homestead" => Ok(Hardfork::Homestead),
+            "TangerineWhistle" | "EIP150" => Ok(Hardfork::TangerineWhistle),
+            "SpuriousDragon" | "EIP158" => Ok(Hardfork::SpuriousDragon),
+            "Byzantium" => Ok(Hardfork::Byzantium),
+            "Constantinople" => Ok(Hardfork::Constantinople),
+            "Petersburg" | "ConstantinopleFix" => Ok(Hardfork::Petersburg),
+            "Istanbul" => Ok(Hardfork::Istanbul),
+            "Berlin" => Ok(Hardfork::Berlin),
+            "London" => Ok(Hardfork::London),
+            "Paris" | "Merge" => Ok(Hardfork::Paris),
+            "Shanghai" => Ok(Hardfork::Shanghai),
+            "Cancun" => Ok(Hardfork::Cancun),
+            "Prague" => Ok(Hardfork::Prague),
+            _ => Err(()),
+        }
+    }
+}
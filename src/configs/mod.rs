@@ -1,3 +1,5 @@
 pub mod bytecode_spec;
+pub mod evm_config;
 pub mod gas_costs;
+pub mod hardfork;
 pub mod precompiles;
@@ -1,35 +1,126 @@
 use lazy_static::lazy_static;
-use primitive_types::U256;
+
+use crate::configs::{ evm_config::EVMConfig, hardfork::Hardfork };
+use crate::runtime::Address;
 
 lazy_static! {
     /// 0x01: ecrecover
-    pub static ref ECRECOVER_PRECOMPILE: U256 = U256::from(1);
+    pub static ref ECRECOVER_PRECOMPILE: Address = Address::from_low_u64_be(1);
 
     /// 0x02: sha256
-    pub static ref SHA256_PRECOMPILE: U256 = U256::from(2);
+    pub static ref SHA256_PRECOMPILE: Address = Address::from_low_u64_be(2);
 
     /// 0x03: ripemd160
-    pub static ref RIPEMD160_PRECOMPILE: U256 = U256::from(3);
+    pub static ref RIPEMD160_PRECOMPILE: Address = Address::from_low_u64_be(3);
 
     /// 0x04: identity
-    pub static ref IDENTITY_PRECOMPILE: U256 = U256::from(4);
+    pub static ref IDENTITY_PRECOMPILE: Address = Address::from_low_u64_be(4);
 
     /// 0x05: modexp (EIP-198)
-    pub static ref MODEXP_PRECOMPILE: U256 = U256::from(5);
+    pub static ref MODEXP_PRECOMPILE: Address = Address::from_low_u64_be(5);
 
     /// 0x06: alt_bn128-add (EIP-196)
-    pub static ref ALTBN128_ADD_PRECOMPILE: U256 = U256::from(6);
+    pub static ref ALTBN128_ADD_PRECOMPILE: Address = Address::from_low_u64_be(6);
 
     /// 0x07: alt_bn128-mul (EIP-196)
-    pub static ref ALTBN128_MUL_PRECOMPILE: U256 = U256::from(7);
+    pub static ref ALTBN128_MUL_PRECOMPILE: Address = Address::from_low_u64_be(7);
 
     /// 0x08: alt_bn128-pairing (EIP-197)
-    pub static ref ALTBN128_PAIRING_PRECOMPILE: U256 = U256::from(8);
+    pub static ref ALTBN128_PAIRING_PRECOMPILE: Address = Address::from_low_u64_be(8);
 
     /// 0x09: blake2-f (EIP-152)
-    pub static ref BLAKE2_F_PRECOMPILE: U256 = U256::from(9);
+    pub static ref BLAKE2_F_PRECOMPILE: Address = Address::from_low_u64_be(9);
+
+    /// 0x0b: bls12-g1add (EIP-2537)
+    pub static ref BLS12_G1ADD_PRECOMPILE: Address = Address::from_low_u64_be(0x0b);
+
+    /// 0x0c: bls12-g1msm (EIP-2537)
+    pub static ref BLS12_G1MSM_PRECOMPILE: Address = Address::from_low_u64_be(0x0c);
+
+    /// 0x0d: bls12-g2add (EIP-2537)
+    pub static ref BLS12_G2ADD_PRECOMPILE: Address = Address::from_low_u64_be(0x0d);
+
+    /// 0x0e: bls12-g2msm (EIP-2537)
+    pub static ref BLS12_G2MSM_PRECOMPILE: Address = Address::from_low_u64_be(0x0e);
+
+    /// 0x0f: bls12-pairing-check (EIP-2537)
+    pub static ref BLS12_PAIRING_PRECOMPILE: Address = Address::from_low_u64_be(0x0f);
+
+    /// 0x10: bls12-map-fp-to-g1 (EIP-2537)
+    pub static ref BLS12_MAP_FP_TO_G1_PRECOMPILE: Address = Address::from_low_u64_be(0x10);
+
+    /// 0x11: bls12-map-fp2-to-g2 (EIP-2537)
+    pub static ref BLS12_MAP_FP2_TO_G2_PRECOMPILE: Address = Address::from_low_u64_be(0x11);
+
+    /// 0x100: p256verify (RIP-7212)
+    pub static ref P256VERIFY_PRECOMPILE: Address = Address::from_low_u64_be(0x100);
+}
+
+pub fn is_precompile(address: &Address, config: &EVMConfig) -> bool {
+    match config.precompiles.get(address) {
+        Some(precompile) => precompile.is_some(),
+        None => is_built_in(address, config),
+    }
+}
+
+fn is_built_in(address: &Address, config: &EVMConfig) -> bool {
+    match introduced_in(address) {
+        Some(hardfork) => config.hardfork.is_enabled(hardfork),
+        None => config.p256_verify && address.eq(&*P256VERIFY_PRECOMPILE),
+    }
+}
+
+/// Hardfork that added the built in precompile at an address, BLAKE2F (0x09) is left out until
+/// it is implemented
+fn introduced_in(address: &Address) -> Option<Hardfork> {
+    if address.ge(&*ECRECOVER_PRECOMPILE) && address.le(&*IDENTITY_PRECOMPILE) {
+        Some(Hardfork::Frontier)
+    } else if address.ge(&*MODEXP_PRECOMPILE) && address.le(&*ALTBN128_PAIRING_PRECOMPILE) {
+        // EIP-198, EIP-196 and EIP-197
+        Some(Hardfork::Byzantium)
+    } else if address.ge(&*BLS12_G1ADD_PRECOMPILE) && address.le(&*BLS12_MAP_FP2_TO_G2_PRECOMPILE) {
+        Some(Hardfork::Prague)
+    } else {
+        None
+    }
 }
 
-pub fn is_precompile(address: &U256) -> bool {
-    address.ge(&*ECRECOVER_PRECOMPILE) && address.le(&*BLAKE2_F_PRECOMPILE)
+/// Addresses of all precompiles, these are warm from the start of a transaction (EIP-2929)
+pub fn addresses(config: &EVMConfig) -> impl Iterator<Item = Address> + '_ {
+    let last_built_in = BLS12_MAP_FP2_TO_G2_PRECOMPILE.to_low_u64_be();
+    let built_in = (ECRECOVER_PRECOMPILE.to_low_u64_be()..=last_built_in)
+        .chain(std::iter::once(P256VERIFY_PRECOMPILE.to_low_u64_be()))
+        .map(Address::from_low_u64_be)
+        .filter(move |address| config.precompiles.get(address).is_none());
+    built_in
+        .chain(config.precompiles.addresses())
+        .filter(move |address| is_precompile(address, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses_in(hardfork: Hardfork) -> Vec<u64> {
+        addresses(&EVMConfig::new(hardfork)).map(|address| address.to_low_u64_be()).collect()
+    }
+
+    #[test]
+    fn test_addresses_per_hardfork() {
+        assert_eq!(addresses_in(Hardfork::Frontier), vec![1, 2, 3, 4]);
+        assert_eq!(addresses_in(Hardfork::Byzantium), (1..=8).collect::<Vec<_>>());
+        assert_eq!(addresses_in(Hardfork::Cancun), (1..=8).collect::<Vec<_>>());
+        assert_eq!(
+            addresses_in(Hardfork::Prague),
+            (1..=8).chain(0x0b..=0x11).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_p256_verify_is_opt_in() {
+        let mut config = EVMConfig::new(Hardfork::Prague);
+        assert!(!is_precompile(&P256VERIFY_PRECOMPILE, &config));
+        config.p256_verify = true;
+        assert!(is_precompile(&P256VERIFY_PRECOMPILE, &config));
+    }
 }
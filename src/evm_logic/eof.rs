@@ -0,0 +1,488 @@
+use std::ops::Range;
+
+use crate::configs::bytecode_spec::opcodes;
+
+/// Magic bytes at the start of every EOF container (EIP-3540)
+pub const EOF_MAGIC: [u8; 2] = [0xEF, 0x00];
+pub const EOF_VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_DATA: u8 = 0xFF;
+const TERMINATOR: u8 = 0x00;
+
+const TYPE_SIZE: usize = 4;
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_STACK_HEIGHT: usize = 1024;
+const MAX_IO: u8 = 0x7F;
+/// Outputs of a code section that never returns to its caller
+pub const NON_RETURNING: u8 = 0x80;
+
+/// Inputs, outputs and maximum stack growth of a code section
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeType {
+    pub inputs: u8,
+    pub outputs: u8,
+    pub max_stack_increase: u16,
+}
+
+/// A parsed EOF container, sections are stored as ranges into the container bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EofContainer {
+    pub types: Vec<CodeType>,
+    pub code_sections: Vec<Range<usize>>,
+    pub data: Range<usize>,
+}
+
+#[inline]
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&EOF_MAGIC)
+}
+
+/// Reads the header and section layout of a container without validating the code
+pub fn parse(code: &[u8]) -> Result<EofContainer, ()> {
+    let mut reader = Reader { bytes: code, offset: 0 };
+    if reader.read_bytes(2)? != EOF_MAGIC || reader.read_u8()? != EOF_VERSION {
+        return Err(());
+    }
+
+    if reader.read_u8()? != KIND_TYPES {
+        return Err(());
+    }
+    let types_size = reader.read_u16()? as usize;
+
+    if reader.read_u8()? != KIND_CODE {
+        return Err(());
+    }
+    let num_code_sections = reader.read_u16()? as usize;
+    if num_code_sections == 0 || num_code_sections > MAX_CODE_SECTIONS {
+        return Err(());
+    }
+    if types_size != num_code_sections * TYPE_SIZE {
+        return Err(());
+    }
+    let mut code_sizes = Vec::with_capacity(num_code_sections);
+    for _ in 0..num_code_sections {
+        let size = reader.read_u16()? as usize;
+        if size == 0 {
+            return Err(());
+        }
+        code_sizes.push(size);
+    }
+
+    // Subcontainers (EIP-7620) are not supported
+    if reader.read_u8()? != KIND_DATA {
+        return Err(());
+    }
+    let data_size = reader.read_u16()? as usize;
+    if reader.read_u8()? != TERMINATOR {
+        return Err(());
+    }
+
+    let mut types = Vec::with_capacity(num_code_sections);
+    for _ in 0..num_code_sections {
+        types.push(CodeType {
+            inputs: reader.read_u8()?,
+            outputs: reader.read_u8()?,
+            max_stack_increase: reader.read_u16()?,
+        });
+    }
+    let mut code_sections = Vec::with_capacity(num_code_sections);
+    for size in code_sizes {
+        let start = reader.offset;
+        reader.read_bytes(size)?;
+        code_sections.push(start..reader.offset);
+    }
+    let data_start = reader.offset;
+    reader.read_bytes(data_size)?;
+    if reader.offset != code.len() {
+        return Err(());
+    }
+
+    Ok(EofContainer {
+        types: types,
+        code_sections: code_sections,
+        data: data_start..code.len(),
+    })
+}
+
+/// Parses a container and validates its types and code, as done before deploying it
+pub fn validate(code: &[u8]) -> Result<EofContainer, ()> {
+    let container = parse(code)?;
+    for (index, code_type) in container.types.iter().enumerate() {
+        if code_type.inputs > MAX_IO ||
+            (code_type.outputs > MAX_IO && code_type.outputs != NON_RETURNING) ||
+            code_type.max_stack_increase as usize > MAX_STACK_HEIGHT - code_type.inputs as usize
+        {
+            return Err(());
+        }
+        if index == 0 && (code_type.inputs != 0 || code_type.outputs != NON_RETURNING) {
+            return Err(());
+        }
+    }
+
+    // Every code section must be reachable from the first one through CALLF
+    let mut reached = vec![false; container.code_sections.len()];
+    let mut to_visit = vec![0];
+    reached[0] = true;
+    while let Some(index) = to_visit.pop() {
+        let called_sections = validate_code_section(code, &container, index)?;
+        for called in called_sections {
+            if !reached[called] {
+                reached[called] = true;
+                to_visit.push(called);
+            }
+        }
+    }
+    if reached.contains(&false) {
+        return Err(());
+    }
+    Ok(container)
+}
+
+/// Validates instructions, jumps and stack heights of a code section (EIP-3670, EIP-4200,
+/// EIP-4750, EIP-5450), returns the sections it calls
+fn validate_code_section(
+    code: &[u8],
+    container: &EofContainer,
+    section_index: usize
+) -> Result<Vec<usize>, ()> {
+    let section = &code[container.code_sections[section_index].clone()];
+    let section_type = &container.types[section_index];
+    let data_size = container.data.len();
+
+    // Find the start of each instruction, checking immediates are complete
+    let mut is_instruction = vec![false; section.len()];
+    let mut called_sections = vec![];
+    let mut has_retf = false;
+    let mut pc = 0;
+    while pc < section.len() {
+        let opcode = section[pc];
+        if stack_io(opcode).is_none() && opcode != opcodes::CALLF && opcode != opcodes::RETF {
+            return Err(());
+        }
+        is_instruction[pc] = true;
+        let immediate_size = immediate_size(section, pc).ok_or(())?;
+        if pc + immediate_size >= section.len() && immediate_size > 0 {
+            return Err(());
+        }
+        match opcode {
+            opcodes::CALLF => {
+                let target = read_u16(section, pc + 1) as usize;
+                if target >= container.types.len() ||
+                    container.types[target].outputs == NON_RETURNING
+                {
+                    return Err(());
+                }
+                called_sections.push(target);
+            }
+            opcodes::RETF => {
+                has_retf = true;
+            }
+            opcodes::DATALOADN => {
+                if (read_u16(section, pc + 1) as usize) + 32 > data_size {
+                    return Err(());
+                }
+            }
+            _ => {}
+        }
+        pc += 1 + immediate_size;
+    }
+    // Returning sections must return and non returning sections must not
+    if has_retf != (section_type.outputs != NON_RETURNING) {
+        return Err(());
+    }
+
+    // Relative jumps must land on an instruction within the section
+    for (pc, _) in is_instruction.iter().enumerate().filter(|(_, is)| **is) {
+        for target in jump_targets(section, pc)? {
+            if target >= section.len() || !is_instruction[target] {
+                return Err(());
+            }
+        }
+    }
+
+    // Single forward pass tracking the range of possible stack heights (EIP-5450)
+    let mut heights: Vec<Option<(usize, usize)>> = vec![None; section.len()];
+    heights[0] = Some((section_type.inputs as usize, section_type.inputs as usize));
+    let mut max_height = section_type.inputs as usize;
+    let mut pc = 0;
+    while pc < section.len() {
+        let opcode = section[pc];
+        let next_pc = pc + 1 + immediate_size(section, pc).ok_or(())?;
+        // Every instruction must be reachable
+        let (min, max) = heights[pc].ok_or(())?;
+        let (inputs, outputs) = match opcode {
+            opcodes::CALLF => {
+                let target = &container.types[read_u16(section, pc + 1) as usize];
+                if max + (target.max_stack_increase as usize) > MAX_STACK_HEIGHT {
+                    return Err(());
+                }
+                (target.inputs as usize, target.outputs as usize)
+            }
+            opcodes::RETF => {
+                if min != max || min != (section_type.outputs as usize) {
+                    return Err(());
+                }
+                (min, 0)
+            }
+            _ => {
+                let (inputs, outputs) = stack_io(opcode).ok_or(())?;
+                (inputs as usize, outputs as usize)
+            }
+        };
+        if min < inputs {
+            return Err(());
+        }
+        let (new_min, new_max) = (min - inputs + outputs, max - inputs + outputs);
+        max_height = max_height.max(new_max);
+
+        let mut successors = jump_targets(section, pc)?;
+        if !is_terminating(opcode) && opcode != opcodes::RJUMP {
+            if next_pc >= section.len() {
+                // Execution can't run off the end of a section
+                return Err(());
+            }
+            successors.push(next_pc);
+        }
+        for successor in successors {
+            if successor > pc {
+                heights[successor] = Some(match heights[successor] {
+                    Some((min, max)) => (min.min(new_min), max.max(new_max)),
+                    None => (new_min, new_max),
+                });
+            } else if heights[successor] != Some((new_min, new_max)) {
+                // Backwards jumps must keep the stack height constant
+                return Err(());
+            }
+        }
+        pc = next_pc;
+    }
+    if max_height > MAX_STACK_HEIGHT ||
+        max_height - (section_type.inputs as usize) != (section_type.max_stack_increase as usize)
+    {
+        return Err(());
+    }
+    Ok(called_sections)
+}
+
+/// Size of the immediate data following the instruction at `pc`, None if it is truncated
+pub fn immediate_size(code: &[u8], pc: usize) -> Option<usize> {
+    Some(match code[pc] {
+        opcodes::PUSH_1..=opcodes::PUSH_32 => (code[pc] - opcodes::PUSH_0) as usize,
+        opcodes::RJUMP | opcodes::RJUMPI | opcodes::CALLF | opcodes::DATALOADN => 2,
+        opcodes::RJUMPV => 1 + 2 * ((*code.get(pc + 1)? as usize) + 1),
+        _ => 0,
+    })
+}
+
+/// Destinations of a relative jump instruction
+fn jump_targets(code: &[u8], pc: usize) -> Result<Vec<usize>, ()> {
+    let relative_targets = match code[pc] {
+        opcodes::RJUMP | opcodes::RJUMPI => vec![(pc + 3, read_i16(code, pc + 1))],
+        opcodes::RJUMPV => {
+            let count = (code[pc + 1] as usize) + 1;
+            let next_pc = pc + 2 + 2 * count;
+            (0..count).map(|index| (next_pc, read_i16(code, pc + 2 + 2 * index))).collect()
+        }
+        _ => vec![],
+    };
+    relative_targets
+        .into_iter()
+        .map(|(base, offset)| {
+            let target = (base as i64) + (offset as i64);
+            if target < 0 { Err(()) } else { Ok(target as usize) }
+        })
+        .collect()
+}
+
+#[inline]
+pub fn read_u16(code: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([code[offset], code[offset + 1]])
+}
+
+#[inline]
+pub fn read_i16(code: &[u8], offset: usize) -> i16 {
+    i16::from_be_bytes([code[offset], code[offset + 1]])
+}
+
+#[inline]
+fn is_terminating(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcodes::STOP | opcodes::RETURN | opcodes::REVERT | opcodes::INVALID | opcodes::RETF
+    )
+}
+
+/// Stack items taken and returned by the instructions allowed in EOF code, None for
+/// instructions that are undefined or only allowed in legacy code
+fn stack_io(opcode: u8) -> Option<(u8, u8)> {
+    Some(match opcode {
+        opcodes::STOP | opcodes::JUMPDEST | opcodes::INVALID | opcodes::RJUMP => (0, 0),
+        | opcodes::ADD
+        | opcodes::MUL
+        | opcodes::SUB
+        | opcodes::DIV
+        | opcodes::SDIV
+        | opcodes::MOD
+        | opcodes::SMOD
+        | opcodes::EXP
+        | opcodes::SIGNEXTEND
+        | opcodes::LT
+        | opcodes::GT
+        | opcodes::SLT
+        | opcodes::SGT
+        | opcodes::EQ
+        | opcodes::AND
+        | opcodes::OR
+        | opcodes::XOR
+        | opcodes::BYTE
+        | opcodes::SHL
+        | opcodes::SHR
+        | opcodes::SAR
+        | opcodes::KECCAK256 => (2, 1),
+        opcodes::ADDMOD | opcodes::MULMOD => (3, 1),
+        | opcodes::ISZERO
+        | opcodes::NOT
+        | opcodes::BALANCE
+        | opcodes::CALLDATALOAD
+        | opcodes::BLOCKHASH
+        | opcodes::BLOBHASH
+        | opcodes::MLOAD
+        | opcodes::SLOAD
+        | opcodes::TLOAD
+        | opcodes::DATALOAD => (1, 1),
+        | opcodes::ADDRESS
+        | opcodes::ORIGIN
+        | opcodes::CALLER
+        | opcodes::CALLVALUE
+        | opcodes::CALLDATASIZE
+        | opcodes::GASPRICE
+        | opcodes::RETURNDATASIZE
+        | opcodes::COINBASE
+        | opcodes::TIMESTAMP
+        | opcodes::NUMBER
+        | opcodes::DIFFICULTY
+        | opcodes::GASLIMIT
+        | opcodes::CHAINID
+        | opcodes::SELFBALANCE
+        | opcodes::BASEFEE
+        | opcodes::BLOBBASEFEE
+        | opcodes::MSIZE
+        | opcodes::DATALOADN
+        | opcodes::DATASIZE => (0, 1),
+        | opcodes::CALLDATACOPY
+        | opcodes::RETURNDATACOPY
+        | opcodes::MCOPY
+        | opcodes::DATACOPY => (3, 0),
+        opcodes::POP | opcodes::RJUMPI | opcodes::RJUMPV => (1, 0),
+        | opcodes::MSTORE
+        | opcodes::MSTORE8
+        | opcodes::SSTORE
+        | opcodes::TSTORE
+        | opcodes::RETURN
+        | opcodes::REVERT => (2, 0),
+        opcodes::PUSH_0..=opcodes::PUSH_32 => (0, 1),
+        opcodes::DUP_1..=opcodes::DUP_16 => {
+            let n = opcode - opcodes::DUP_1 + 1;
+            (n, n + 1)
+        }
+        opcodes::SWAP_1..=opcodes::SWAP_16 => {
+            let n = opcode - opcodes::SWAP_1 + 2;
+            (n, n)
+        }
+        opcodes::LOG_0..=opcodes::LOG_4 => (opcode - opcodes::LOG_0 + 2, 0),
+        opcodes::EXTCALL => (4, 1),
+        _ => {
+            return None;
+        }
+    })
+}
+
+/// Reads the header fields of a container in order
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], ()> {
+        let end = self.offset.checked_add(size).ok_or(())?;
+        let bytes = self.bytes.get(self.offset..end).ok_or(())?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ()> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ()> {
+        Ok(read_u16(self.read_bytes(2)?, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a container from (inputs, outputs, max stack increase, code) sections
+    fn container(sections: &[(u8, u8, u16, Vec<u8>)], data: &[u8]) -> Vec<u8> {
+        let mut code = vec![0xef, 0x00, EOF_VERSION, KIND_TYPES];
+        code.extend(((sections.len() * TYPE_SIZE) as u16).to_be_bytes());
+        code.push(KIND_CODE);
+        code.extend((sections.len() as u16).to_be_bytes());
+        for (_, _, _, section) in sections {
+            code.extend((section.len() as u16).to_be_bytes());
+        }
+        code.push(KIND_DATA);
+        code.extend((data.len() as u16).to_be_bytes());
+        code.push(TERMINATOR);
+        for (inputs, outputs, max_stack_increase, _) in sections {
+            code.extend([*inputs, *outputs]);
+            code.extend(max_stack_increase.to_be_bytes());
+        }
+        for (_, _, _, section) in sections {
+            code.extend(section);
+        }
+        code.extend(data);
+        code
+    }
+
+    #[test]
+    fn test_validate_valid_container() {
+        // PUSH1 7 CALLF 1 POP DATALOADN 0 POP STOP, section 1 doubles its input
+        let section = vec![0x60, 0x07, 0xe3, 0x00, 0x01, 0x50, 0xd1, 0x00, 0x00, 0x50, 0x00];
+        let code = container(
+            &[(0, NON_RETURNING, 1, section), (1, 1, 1, vec![0x80, 0x01, 0xe4])],
+            &[0x42; 32]
+        );
+        let container = validate(&code).unwrap();
+        assert_eq!(container.code_sections.len(), 2);
+        assert_eq!(container.data.len(), 32);
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_code() {
+        // RETF in the first section, which is never entered with CALLF
+        assert!(validate(&container(&[(0, NON_RETURNING, 0, vec![0xe4])], &[])).is_err());
+        // DATALOADN past the end of the data section
+        let dataloadn = vec![0xd1, 0x00, 0x01, 0x50, 0x00];
+        assert!(validate(&container(&[(0, NON_RETURNING, 1, dataloadn)], &[0; 32])).is_err());
+        // RJUMP into the immediate of PUSH1
+        let rjump = vec![0x60, 0x00, 0x50, 0xe0, 0xff, 0xfb, 0x00];
+        assert!(validate(&container(&[(0, NON_RETURNING, 1, rjump)], &[])).is_err());
+        // CALLF to a section that doesn't exist
+        let callf = vec![0xe3, 0x00, 0x01, 0x00];
+        assert!(validate(&container(&[(0, NON_RETURNING, 0, callf)], &[])).is_err());
+        // Legacy instructions aren't allowed
+        assert!(validate(&container(&[(0, NON_RETURNING, 0, vec![0x56])], &[])).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_unreachable_section() {
+        let code = container(&[(0, NON_RETURNING, 0, vec![0x00]), (0, 0, 0, vec![0xe4])], &[]);
+        assert!(parse(&code).is_ok());
+        assert!(validate(&code).is_err());
+    }
+}
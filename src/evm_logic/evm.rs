@@ -1,34 +1,49 @@
+mod authorization;
 mod call;
 mod create;
 mod decoder;
+mod log;
+mod receipt;
 pub mod macros;
 pub mod precompiles;
 
 use std::f32::consts::E;
 
-use crate::configs::gas_costs::static_costs;
-use crate::configs::precompiles::{ self as precompile_addresses, is_precompile };
+use crate::configs::bytecode_spec::MAX_INITCODE_SIZE;
+use crate::configs::gas_costs::{ static_costs, DynamicCosts };
+use crate::configs::evm_config::EVMConfig;
+use crate::configs::hardfork::Hardfork;
+use crate::configs::precompiles::{ self as precompile_addresses };
 use crate::evm_logic::gas_recorder::GasRecorder;
-use crate::result::{ ExecutionError, ExecutionResult, ExecutionSuccess };
-use crate::runtime::Runtime;
+use crate::result::{ ExecutionError, ExecutionResult, ExecutionSuccess, FatalError };
+use crate::runtime::{ Address, Runtime, StorageKey };
 
 use super::state::memory::Memory;
 use super::state::program_memory::ProgramMemory;
 use super::state::stack::Stack;
 use super::util::ZERO;
 
-use precompiles::ecrecover::{ ecrecover_contract };
-use precompiles::sha2_256::sha2_256_contract;
-use primitive_types::U256;
+pub use authorization::Authorization;
+pub use log::Log;
+pub use receipt::{ logs_bloom, logs_hash, receipts_root, Receipt };
+use primitive_types::{ H256, U256 };
 
 #[derive(Clone)]
 struct Transaction {
-    pub origin: U256,
+    pub origin: Address,
     pub gas_price: U256,
+    pub blob_hashes: Vec<H256>,
+}
+
+/// An entry of an access list (EIP-2930), warmed before the transaction is executed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<StorageKey>,
 }
 
 struct Message {
-    pub caller: U256,
+    pub caller: Address,
     pub value: U256,
     pub data: Vec<u8>,
 }
@@ -38,7 +53,7 @@ pub struct EVMContext {
     memory: Memory,
     program: ProgramMemory,
     program_counter: usize,
-    contract_address: U256,
+    contract_address: Address,
     transaction: Transaction,
     message: Message,
     last_return_data: Memory,
@@ -47,25 +62,39 @@ pub struct EVMContext {
     nested_index: usize,
     gas_recorder: GasRecorder,
     is_static: bool,
+    config: EVMConfig,
+    // Return addresses of the EOF code sections entered with CALLF
+    return_stack: Vec<usize>,
+    // Accounts created so far in the transaction, needed for SELFDESTRUCT (EIP-6780), in creation
+    // order so that the ones created by a failed call can be truncated
+    created_accounts: Vec<Address>,
+    // Logs emitted by this call and the calls it made that succeeded
+    logs: Vec<Log>,
 }
 
 impl EVMContext {
     #[inline]
     fn create_sub_context(
-        address: U256,
+        address: Address,
         message: Message,
         gas: u64,
         code: Vec<u8>,
         transaction: Transaction,
         gas_price: U256,
         nested_index: usize,
-        is_static: bool
+        is_static: bool,
+        config: EVMConfig
     ) -> EVMContext {
+        let program = if config.eof {
+            ProgramMemory::from_eof(&code)
+        } else {
+            ProgramMemory::from(&code)
+        };
         EVMContext {
             stack: Stack::new(),
             memory: Memory::new(),
-            program: ProgramMemory::from(&code),
-            program_counter: 0,
+            program_counter: program.entry_point(),
+            program: program,
             contract_address: address,
             transaction: transaction,
             message: message,
@@ -79,12 +108,20 @@ impl EVMContext {
                 gas_refunds: 0,
             },
             is_static: is_static,
+            config: config,
+            return_stack: vec![],
+            created_accounts: Vec::new(),
+            logs: vec![],
         }
     }
 
     #[inline]
-    fn execute_program(&mut self, runtime: &mut impl Runtime, debug: bool) -> ExecutionResult {
-        runtime.add_context();
+    fn execute_program<R: Runtime>(
+        &mut self,
+        runtime: &mut R,
+        debug: bool
+    ) -> Result<ExecutionResult, R::Error> {
+        runtime.add_context()?;
 
         // let num_none_zero_calldata = self.message.data.iter().filter(|x| **x != 0).count() as u64;
         // let num_zero_calldata = self.message.data.len() as u64 - num_none_zero_calldata as u64;
@@ -92,33 +129,11 @@ impl EVMContext {
         // let calldata_cost = static_costs::G_ZERO + static_costs::G_TX_DATA_NON_ZERO * num_none_zero_calldata + static_costs::G_TX_DATA_ZERO * num_zero_calldata;
         // self.gas_recorder.record_gas_usage(calldata_cost as u64);
 
-        // TODO here we could check if a precompile and if so run the precompile contract
-        let mut result;
         println!("Contract Address: {:x} Ecrecover", self.contract_address);
-        match self.contract_address {
-            x if x.eq(&precompile_addresses::ECRECOVER_PRECOMPILE) => {
-                result = ecrecover_contract(self);
-            }
-            x if x == *precompile_addresses::SHA256_PRECOMPILE => {
-                result = sha2_256_contract(self);
-            }
-            _ => {
-                let jump_dests = decoder::calculate_jump_dests(self);
-                if self.program.len() != 0 {
-                    loop {
-                        result = self.execute_next_instruction(runtime, &jump_dests, debug);
-                        match &result {
-                            ExecutionResult::InProgress => {}
-                            _ => {
-                                break;
-                            }
-                        }
-                    }
-                } else {
-                    result = ExecutionResult::Error(ExecutionError::InvalidMemSize);
-                }
-            }
-        }
+        let result = match precompiles::execute_precompile(self, runtime)? {
+            Some(result) => result,
+            None => self.execute_code(runtime, debug)?,
+        };
 
         // TODO move this into gas_recorder
         self.gas_recorder.gas_usage = (if self.gas_recorder.gas_usage > (self.gas_input as usize) {
@@ -132,65 +147,178 @@ impl EVMContext {
         }
         match result {
             ExecutionResult::Success(_) => {
-                runtime.merge_context();
+                runtime.merge_context()?;
             }
             ExecutionResult::Error(_) => {
-                runtime.revert_context();
+                runtime.revert_context()?;
             }
             ExecutionResult::InProgress => {
                 panic!("Program shouldn't have excited in progress");
             }
         }
-        result
+        Ok(result)
+    }
+
+    #[inline]
+    fn execute_code<R: Runtime>(
+        &mut self,
+        runtime: &mut R,
+        debug: bool
+    ) -> Result<ExecutionResult, R::Error> {
+        let jump_dests = decoder::calculate_jump_dests(self);
+        // Running empty code is the same as a STOP
+        if self.program.len() == 0 {
+            return Ok(ExecutionResult::Success(ExecutionSuccess::Stop));
+        }
+        // Containers are validated before any of their code runs, including init code
+        if self.config.eof && self.program.is_invalid_eof() {
+            self.gas_recorder.set_gas_usage_to_max();
+            return Ok(ExecutionResult::Error(ExecutionError::InvalidEofContainer));
+        }
+        loop {
+            let result = self.execute_next_instruction(runtime, &jump_dests, debug)?;
+            match &result {
+                ExecutionResult::InProgress => {}
+                _ => {
+                    return Ok(result);
+                }
+            }
+        }
     }
 
     #[inline]
-    fn execute_next_instruction(
+    fn execute_next_instruction<R: Runtime>(
         &mut self,
-        runtime: &mut impl Runtime,
+        runtime: &mut R,
         jump_dests: &[usize],
         debug: bool
-    ) -> ExecutionResult {
+    ) -> Result<ExecutionResult, R::Error> {
         decoder::decode_instruction(self, runtime, jump_dests, debug)
     }
 
     #[inline]
     fn check_gas_usage(&self) -> ExecutionResult {
-        match !self.gas_recorder.is_valid_with_refunds() {
+        match !self.gas_recorder.is_valid() {
             true => ExecutionResult::Error(ExecutionError::InsufficientGas),
             false => ExecutionResult::InProgress,
         }
     }
 }
 
+/// Address an account's code delegates to, delegations only exist from Prague (EIP-7702)
+#[inline]
+fn delegation_target(hardfork: Hardfork, code: &[u8]) -> Option<Address> {
+    if !hardfork.is_enabled(Hardfork::Prague) {
+        return None;
+    }
+    authorization::delegation_target(code)
+}
+
+/// Code run by an account delegating to `target`, precompiles and missing accounts have none
+#[inline]
+fn delegated_code<R: Runtime>(
+    runtime: &R,
+    config: &EVMConfig,
+    target: Address
+) -> Result<Vec<u8>, R::Error> {
+    if precompile_addresses::is_precompile(&target, config) || !runtime.exists(target)? {
+        return Ok(vec![]);
+    }
+    runtime.code(target)
+}
+
 // copy between mem objects
 // message data
 // program data
 // mem data
+/// Returns the result, the gas used after refunds and the logs, which are only kept if the
+/// transaction succeeds, or a fatal error if the runtime fails
 #[inline]
-pub fn execute_transaction(
-    runtime: &mut impl Runtime,
-    contract_address: U256,
-    origin: U256,
+pub fn execute_transaction<R: Runtime>(
+    runtime: &mut R,
+    config: &EVMConfig,
+    contract_address: Option<Address>,
+    origin: Address,
     gas: u64,
-    gas_price: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
     value: U256,
     data: &[u8],
+    blob_hashes: &[H256],
+    access_list: &[AccessListItem],
+    authorization_list: &[Authorization],
     debug: bool
-) -> (ExecutionResult, usize) {
+) -> Result<(ExecutionResult, usize, Vec<Log>), FatalError<R::Error>> {
+    let hardfork = config.hardfork;
+    // A transaction without a target creates a contract, the data is then the init code
+    let is_create = contract_address.is_none();
+    if is_create && hardfork.is_enabled(Hardfork::Shanghai) && data.len() > MAX_INITCODE_SIZE {
+        // Invalid transaction, so no state is modified and no gas is charged
+        return Ok((ExecutionResult::Error(ExecutionError::InitCodeSizeExceeded), 0, vec![]));
+    }
+    // Legacy transactions pass their gas price as both the max fee and the max priority fee
+    let (gas_price, priority_fee_per_gas) = if hardfork.is_enabled(Hardfork::London) {
+        // EIP-1559
+        let base_fee = runtime.block_base_fee_per_gas()?;
+        if max_fee_per_gas < base_fee || max_priority_fee_per_gas > max_fee_per_gas {
+            return Ok((ExecutionResult::Error(ExecutionError::InvalidGasPrice), 0, vec![]));
+        }
+        let gas_price = max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas));
+        (gas_price, gas_price - base_fee)
+    } else {
+        (max_fee_per_gas, max_fee_per_gas)
+    };
+    let contract_address = match contract_address {
+        Some(address) => address,
+        None => create::create_address(origin, runtime.nonce(origin)?),
+    };
+    runtime.increase_nonce(origin)?;
+
     let message = Message {
-        caller: contract_address,
+        caller: origin,
         value: value,
-        data: data.to_vec(),
+        data: if is_create { vec![] } else { data.to_vec() },
     };
 
     let transaction = Transaction {
         origin: origin,
         gas_price: gas_price,
+        blob_hashes: blob_hashes.to_vec(),
     };
-    if !is_precompile(&contract_address) {
-        runtime.mark_hot(contract_address);
+    // Warm the accounts and storage keys that are accessed cheaply from the start (EIP-2929)
+    runtime.mark_hot(origin)?;
+    runtime.mark_hot(contract_address)?;
+    for address in precompile_addresses::addresses(config) {
+        runtime.mark_hot(address)?;
     }
+    if hardfork.is_enabled(Hardfork::Shanghai) {
+        // EIP-3651
+        runtime.mark_hot(runtime.block_coinbase()?)?;
+    }
+    for item in access_list {
+        runtime.mark_hot(item.address)?;
+        for key in &item.storage_keys {
+            runtime.mark_hot_index(item.address, *key)?;
+        }
+    }
+    // Set code transactions (EIP-7702), applied even if the execution later fails
+    let authorization_refund = if hardfork.is_enabled(Hardfork::Prague) {
+        authorization::process_authorizations(runtime, authorization_list)?
+    } else {
+        0
+    };
+    let code = if is_create {
+        data.to_vec()
+    } else {
+        let code = runtime.code(contract_address)?;
+        match delegation_target(hardfork, &code) {
+            Some(target) => {
+                runtime.mark_hot(target)?;
+                delegated_code(runtime, config, target)?
+            }
+            None => code,
+        }
+    };
 
     // println!("Origin: {:x}", origin);
     // println!("Contract Address: {:x}", contract_address);
@@ -198,47 +326,111 @@ pub fn execute_transaction(
         contract_address,
         message,
         gas,
-        runtime.code(contract_address),
+        code,
         transaction,
         gas_price,
         0,
-        false
+        false,
+        config.clone()
     );
 
     evm.gas_recorder.record_gas_usage(static_costs::G_TRANSACTION);
-    evm.gas_recorder.record_call_data_gas_usage(&evm.message.data);
+    evm.gas_recorder.record_call_data_gas_usage(data, hardfork);
+    for item in access_list {
+        evm.gas_recorder.record_gas_usage(
+            static_costs::G_ACCESS_LIST_ADDRESS +
+                static_costs::G_ACCESS_LIST_STORAGE * (item.storage_keys.len() as u64)
+        );
+    }
+    evm.gas_recorder.record_gas_usage(
+        static_costs::G_AUTHORIZATION * (authorization_list.len() as u64)
+    );
+    evm.gas_recorder.record_refund(authorization_refund);
+    if is_create {
+        if hardfork.is_enabled(Hardfork::Homestead) {
+            evm.gas_recorder.record_gas_usage(static_costs::G_TX_CREATE);
+        }
+        if hardfork.is_enabled(Hardfork::Shanghai) {
+            evm.gas_recorder.record_gas_usage((DynamicCosts::InitCode { size: data.len() }).cost(hardfork));
+        }
+    }
     if debug {
         println!("Call Data Gas Cost: {:x}", evm.gas_recorder.gas_usage);
     }
 
+    if is_create {
+        evm.created_accounts.push(contract_address);
+        runtime.create_contract(contract_address, vec![])?;
+        if hardfork.is_enabled(Hardfork::SpuriousDragon) {
+            runtime.increase_nonce(contract_address)?;
+        }
+    }
+
     // println!("Value: {:x}", value);
     // TODO checks here on balance
-    runtime.deposit(contract_address, value);
+    runtime.deposit(contract_address, value)?;
     // withdraw the value from the sender
-    runtime.withdrawal(origin, value);
-    let result = evm.execute_program(runtime, debug);
-    let gas_usage = evm.gas_recorder.usage_with_refunds();
-
-    // Increase Nonce and deposit the value
-    runtime.increase_nonce(origin);
+    runtime.withdrawal(origin, value)?;
+    if is_create {
+        // Allows the init code's changes to be reverted if the code can't be deployed
+        runtime.add_context()?;
+    }
+    let mut result = evm.execute_program(runtime, debug)?;
+    if is_create {
+        let deployed_code = match &result {
+            ExecutionResult::Success(ExecutionSuccess::Return(code)) => Some(code.clone()),
+            ExecutionResult::Success(_) => Some(vec![]),
+            _ => None,
+        };
+        match deployed_code.map(|code| create::deposit_code(config, &mut evm.gas_recorder, code)) {
+            Some(Ok(code)) => {
+                // The init code may have self destructed
+                if runtime.exists(contract_address)? {
+                    runtime.set_contract_code(contract_address, code)?;
+                }
+                runtime.merge_context()?;
+            }
+            Some(Err(error)) => {
+                evm.gas_recorder.set_gas_usage_to_max();
+                runtime.revert_context()?;
+                result = ExecutionResult::Error(error);
+            }
+            None => {
+                runtime.revert_context()?;
+            }
+        }
+    }
+    // Execution refunds only apply if the transaction succeeds, authorizations are processed regardless
+    if !matches!(result, ExecutionResult::Success(_)) {
+        evm.gas_recorder.gas_refunds = authorization_refund;
+    }
+    let gas_usage = evm.gas_recorder.usage_with_refunds(hardfork);
 
-    match result {
+    match &result {
         ExecutionResult::Success(_) => {}
         _ => {
+            evm.logs.clear();
             // Undo the value send, TODO fix this up
-            runtime.deposit(origin, value);
+            runtime.deposit(origin, value)?;
             // withdraw the value from the sender
-            runtime.withdrawal(contract_address, value);
+            runtime.withdrawal(contract_address, value)?;
+            if is_create {
+                runtime.mark_delete(contract_address)?;
+            }
         }
     }
 
-    // Withdraw the gas from the wallet
-    let eth_usage = gas_usage * gas_price.as_usize();
-    runtime.withdrawal(origin, U256::from(eth_usage as u64));
-    runtime.deposit(runtime.block_coinbase(), U256::from(eth_usage as u64));
+    // Withdraw the gas from the wallet, only the priority fee goes to the coinbase and the rest
+    // of the fee is burnt
+    runtime.withdrawal(origin, U256::from(gas_usage).saturating_mul(gas_price))?;
+    runtime.deposit(
+        runtime.block_coinbase()?,
+        U256::from(gas_usage).saturating_mul(priority_fee_per_gas)
+    )?;
 
     // TODO handle not enough eth for gas and value
 
-    runtime.merge_context();
-    return (result, gas_usage);
+    runtime.clear_transient_storage()?;
+    runtime.merge_context()?;
+    return Ok((result, gas_usage, evm.logs));
 }
@@ -0,0 +1,118 @@
+use lazy_static::lazy_static;
+use primitive_types::U256;
+
+use crate::configs::gas_costs::static_costs;
+use crate::evm_logic::util::{ h256_to_u256, keccak256, u256_to_address };
+use crate::runtime::{ Address, Runtime };
+
+use super::precompiles::ecrecover::ecrecover;
+
+/// Prefix of the code of an account that delegates to another account (EIP-7702)
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Magic byte prepended to an authorization before hashing it for signing
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+lazy_static! {
+    /// Half of the secp256k1 curve order, signatures with a larger s are malleable (EIP-2)
+    static ref SECP256K1N_HALF: U256 = U256::from_str_radix(
+        "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0",
+        16
+    ).unwrap();
+}
+
+/// A signed authorization from a set code transaction (EIP-7702), allowing the signer's account
+/// to execute the code at `address`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Authorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U256,
+    pub y_parity: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Authorization {
+    /// Recovers the account that signed the authorization, None if the signature is invalid
+    pub fn authority(&self) -> Option<Address> {
+        if self.y_parity > U256::one() || self.s > *SECP256K1N_HALF {
+            return None;
+        }
+        let mut stream = rlp::RlpStream::new_list(3);
+        stream.append(&self.chain_id);
+        stream.append(&self.address);
+        stream.append(&self.nonce);
+        let mut message = vec![AUTHORIZATION_MAGIC];
+        message.extend_from_slice(&stream.out());
+        let hash = h256_to_u256(keccak256(&message));
+        let authority = ecrecover(hash, U256::from(27) + self.y_parity, self.r, self.s);
+        if authority.is_zero() { None } else { Some(u256_to_address(authority)) }
+    }
+}
+
+/// Returns the address an account's code delegates to, if the code is a delegation designator
+pub fn delegation_target(code: &[u8]) -> Option<Address> {
+    if code.len() != DELEGATION_PREFIX.len() + 20 || !code.starts_with(&DELEGATION_PREFIX) {
+        return None;
+    }
+    Some(Address::from_slice(&code[DELEGATION_PREFIX.len()..]))
+}
+
+/// Code that makes an account delegate to `address`
+pub fn delegation_designator(address: Address) -> Vec<u8> {
+    let mut code = DELEGATION_PREFIX.to_vec();
+    code.extend_from_slice(address.as_bytes());
+    code
+}
+
+/// Applies the authorizations of a set code transaction, skipping invalid ones,
+/// returns the gas refunded for authorities that already existed
+pub fn process_authorizations<R: Runtime>(
+    runtime: &mut R,
+    authorizations: &[Authorization]
+) -> Result<i64, R::Error> {
+    let mut refund = 0;
+    for authorization in authorizations {
+        if !authorization.chain_id.is_zero() && authorization.chain_id != runtime.chain_id()? {
+            continue;
+        }
+        if authorization.nonce >= U256::from(u64::MAX) {
+            continue;
+        }
+        let authority = match authorization.authority() {
+            Some(authority) => authority,
+            None => {
+                continue;
+            }
+        };
+        runtime.mark_hot(authority)?;
+        let exists = runtime.exists(authority)?;
+        if exists {
+            let code = runtime.code(authority)?;
+            if !code.is_empty() && delegation_target(&code).is_none() {
+                continue;
+            }
+        }
+        if runtime.nonce(authority)? != authorization.nonce {
+            continue;
+        }
+        // Only the base cost is kept for accounts that didn't need to be created
+        if exists {
+            refund += (static_costs::G_AUTHORIZATION - static_costs::G_AUTHORIZATION_BASE) as i64;
+        }
+        // Delegating to the zero address clears the delegation
+        let code = if authorization.address.is_zero() {
+            vec![]
+        } else {
+            delegation_designator(authorization.address)
+        };
+        if exists {
+            runtime.set_contract_code(authority, code)?;
+        } else {
+            runtime.create_contract(authority, code)?;
+        }
+        runtime.increase_nonce(authority)?;
+    }
+    Ok(refund)
+}
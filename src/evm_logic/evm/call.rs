@@ -1,23 +1,33 @@
 use core::panic;
+use std::mem;
 
-use super::macros::pop_u64;
-use super::{ macros::pop, EVMContext, Message };
-use crate::configs::gas_costs::static_costs::G_CALL_STIPEND;
+use super::macros::{ pop_address, pop_u64, pop_usize };
+use super::{ delegated_code, delegation_target, macros::pop, EVMContext, Message };
+use crate::configs::gas_costs::static_costs::{
+    G_CALL_STIPEND,
+    G_EXTCALL_MIN_CALLEE,
+    G_EXTCALL_MIN_RETAINED,
+};
 use crate::configs::gas_costs::DynamicCosts;
+use crate::configs::hardfork::Hardfork;
 use crate::configs::precompiles::is_precompile;
 use crate::evm_logic::evm::macros::{ push, return_if_error, return_if_gas_too_high };
 use crate::evm_logic::state::memory::Memory;
-use crate::evm_logic::util::ZERO;
+use crate::evm_logic::util::{ u256_to_address, ZERO };
 use crate::result::{ ExecutionError, ExecutionResult, ExecutionSuccess };
-use crate::runtime::Runtime;
+use crate::runtime::{ Address, Runtime };
 
 use primitive_types::U256;
 
 #[inline]
-pub fn call(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -> ExecutionResult {
+pub fn call<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool
+) -> Result<ExecutionResult, R::Error> {
     let (gas, address, value, args_offset, args_size, ret_offset, ret_size) = (
         pop!(evm),
-        pop!(evm),
+        pop_address!(evm),
         pop!(evm),
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
@@ -43,31 +53,40 @@ pub fn call(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -> Ex
     evm.gas_recorder.record_gas_usage(
         (DynamicCosts::Call {
             value: value,
-            target_is_cold: !is_precompile(&call_args.code_address) && runtime.is_cold(address),
-            empty_account: is_precompile(&call_args.code_address) ||
-            (!value.eq(&U256::zero()) &&
-                runtime.nonce(address).eq(&U256::zero()) &&
-                runtime.code_size(address).eq(&U256::zero()) &&
-                runtime.balance(address).eq(&U256::zero())),
+            target_is_cold: !is_precompile(&call_args.code_address, &evm.config) && runtime.is_cold(address)?,
+            empty_account: if evm.config.hardfork.is_enabled(Hardfork::SpuriousDragon) {
+                is_precompile(&call_args.code_address, &evm.config) ||
+                    (!value.eq(&U256::zero()) &&
+                        runtime.nonce(address)?.eq(&U256::zero()) &&
+                        runtime.code_size(address)?.eq(&U256::zero()) &&
+                        runtime.balance(address)?.eq(&U256::zero()))
+            } else {
+                // Before EIP-161 only accounts missing from the state are new
+                !runtime.exists(address)?
+            },
             is_delegate: false,
             is_code: false,
-        }).cost()
+        }).cost(evm.config.hardfork)
     );
     return_if_gas_too_high!(evm.gas_recorder);
-    match make_call(evm, runtime, debug, call_args, false) {
+    Ok(match make_call(evm, runtime, debug, call_args, false)? {
         ExecutionResult::Error(_) => {
             ExecutionResult::Success(ExecutionSuccess::RevertedTransaction)
         }
         ExecutionResult::Success(_) => ExecutionResult::InProgress,
         ExecutionResult::InProgress => panic!("Call should not be still in progress"),
-    }
+    })
 }
 
 #[inline]
-pub fn call_code(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -> ExecutionResult {
+pub fn call_code<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool
+) -> Result<ExecutionResult, R::Error> {
     let (gas, address, value, args_offset, args_size, ret_offset, ret_size) = (
         pop!(evm),
-        pop!(evm),
+        pop_address!(evm),
         pop!(evm),
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
@@ -94,39 +113,39 @@ pub fn call_code(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool)
     evm.gas_recorder.record_gas_usage(
         (DynamicCosts::Call {
             value: value,
-            target_is_cold: !is_precompile(&call_args.code_address) && runtime.is_cold(address),
-            empty_account: is_precompile(&call_args.code_address) ||
+            target_is_cold: !is_precompile(&call_args.code_address, &evm.config) && runtime.is_cold(address)?,
+            empty_account: is_precompile(&call_args.code_address, &evm.config) ||
             (!value.eq(&U256::zero()) &&
-                runtime.nonce(address).eq(&U256::zero()) &&
-                runtime.code_size(address).eq(&U256::zero()) &&
-                runtime.balance(address).eq(&U256::zero())),
+                runtime.nonce(address)?.eq(&U256::zero()) &&
+                runtime.code_size(address)?.eq(&U256::zero()) &&
+                runtime.balance(address)?.eq(&U256::zero())),
             is_delegate: false,
             is_code: true,
-        }).cost()
+        }).cost(evm.config.hardfork)
     );
 
     if call_args.value != ZERO {
         call_args.gas += G_CALL_STIPEND;
     }
     return_if_gas_too_high!(evm.gas_recorder);
-    match make_call(evm, runtime, debug, call_args, false) {
+    Ok(match make_call(evm, runtime, debug, call_args, false)? {
         ExecutionResult::Error(_) => {
             ExecutionResult::Success(ExecutionSuccess::RevertedTransaction)
         }
         ExecutionResult::Success(_) => ExecutionResult::InProgress,
         ExecutionResult::InProgress => panic!("Call should not be still in progress"),
-    }
+    })
 }
 
 #[inline]
-pub fn delegate_call(
+pub fn delegate_call<R: Runtime>(
     evm: &mut EVMContext,
-    runtime: &mut impl Runtime,
+    runtime: &mut R,
     debug: bool
-) -> ExecutionResult {
+) -> Result<ExecutionResult, R::Error> {
     let (gas, address, args_offset, args_size, ret_offset, ret_size) = (
         pop!(evm),
-        pop!(evm),
+        pop_address!(evm),
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
@@ -153,36 +172,36 @@ pub fn delegate_call(
     evm.gas_recorder.record_gas_usage(
         (DynamicCosts::Call {
             value: evm.message.value,
-            target_is_cold: !is_precompile(&call_args.code_address) && runtime.is_cold(address),
-            empty_account: is_precompile(&call_args.code_address) ||
+            target_is_cold: !is_precompile(&call_args.code_address, &evm.config) && runtime.is_cold(address)?,
+            empty_account: is_precompile(&call_args.code_address, &evm.config) ||
             (!evm.message.value.eq(&U256::zero()) &&
-                runtime.nonce(address).eq(&U256::zero()) &&
-                runtime.code_size(address).eq(&U256::zero()) &&
-                runtime.balance(address).eq(&U256::zero())),
+                runtime.nonce(address)?.eq(&U256::zero()) &&
+                runtime.code_size(address)?.eq(&U256::zero()) &&
+                runtime.balance(address)?.eq(&U256::zero())),
             is_delegate: true,
             is_code: false,
-        }).cost()
+        }).cost(evm.config.hardfork)
     );
 
     return_if_gas_too_high!(evm.gas_recorder);
-    match make_call(evm, runtime, debug, call_args, false) {
+    Ok(match make_call(evm, runtime, debug, call_args, false)? {
         ExecutionResult::Error(_) => {
             ExecutionResult::Success(ExecutionSuccess::RevertedTransaction)
         }
         ExecutionResult::Success(_) => ExecutionResult::InProgress,
         ExecutionResult::InProgress => panic!("Call should not be still in progress"),
-    }
+    })
 }
 
 #[inline]
-pub fn static_call(
+pub fn static_call<R: Runtime>(
     evm: &mut EVMContext,
-    runtime: &mut impl Runtime,
+    runtime: &mut R,
     debug: bool
-) -> ExecutionResult {
+) -> Result<ExecutionResult, R::Error> {
     let (gas, address, args_offset, args_size, ret_offset, ret_size) = (
         pop!(evm),
-        pop!(evm),
+        pop_address!(evm),
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
         pop_u64!(evm) as usize,
@@ -206,34 +225,125 @@ pub fn static_call(
     evm.gas_recorder.record_gas_usage(
         (DynamicCosts::Call {
             value: ZERO,
-            target_is_cold: !is_precompile(&call_args.code_address) && runtime.is_cold(address),
-            empty_account: is_precompile(&call_args.code_address) ||
+            target_is_cold: !is_precompile(&call_args.code_address, &evm.config) && runtime.is_cold(address)?,
+            empty_account: is_precompile(&call_args.code_address, &evm.config) ||
             (!evm.message.value.eq(&U256::zero()) &&
-                runtime.nonce(address).eq(&U256::zero()) &&
-                runtime.code_size(address).eq(&U256::zero()) &&
-                runtime.balance(address).eq(&U256::zero())),
+                runtime.nonce(address)?.eq(&U256::zero()) &&
+                runtime.code_size(address)?.eq(&U256::zero()) &&
+                runtime.balance(address)?.eq(&U256::zero())),
             is_delegate: true,
             is_code: false,
-        }).cost()
+        }).cost(evm.config.hardfork)
     );
     return_if_gas_too_high!(evm.gas_recorder);
-    if !is_precompile(&address) {
-        runtime.mark_hot(address);
+    if !is_precompile(&address, &evm.config) {
+        runtime.mark_hot(address)?;
     }
-    match make_call(evm, runtime, debug, call_args, true) {
+    Ok(match make_call(evm, runtime, debug, call_args, true)? {
         ExecutionResult::Error(_) => {
             ExecutionResult::Success(ExecutionSuccess::RevertedTransaction)
         }
         ExecutionResult::Success(_) => ExecutionResult::InProgress,
         ExecutionResult::InProgress => panic!("Call should not be still in progress"),
+    })
+}
+
+/// EXTCALL (EIP-7069), pushes 0 on success, 1 on revert or a call that couldn't be made and
+/// 2 on failure
+#[inline]
+pub fn ext_call<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool
+) -> Result<ExecutionResult, R::Error> {
+    let (address, args_offset, args_size, value) = (
+        pop!(evm),
+        pop_usize!(evm),
+        pop_usize!(evm),
+        pop!(evm),
+    );
+    if address.bits() > 160 {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return Ok(ExecutionResult::Error(ExecutionError::InvalidAddress));
+    }
+    let address = u256_to_address(address);
+    if evm.is_static && !value.is_zero() {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return Ok(ExecutionResult::Error(ExecutionError::ModifyStaticState));
+    }
+    evm.gas_recorder.record_gas_usage(
+        (DynamicCosts::ExtCall {
+            value: value,
+            target_is_cold: !is_precompile(&address, &evm.config) && runtime.is_cold(address)?,
+            empty_account: runtime.nonce(address)?.is_zero() &&
+            runtime.code_size(address)?.is_zero() &&
+            runtime.balance(address)?.is_zero(),
+        }).cost(evm.config.hardfork)
+    );
+    if args_size != 0 {
+        match args_offset.checked_add(args_size) {
+            Some(end) => {
+                return_if_error!(evm.memory.expand(end, Some(&mut evm.gas_recorder)));
+            }
+            None => {
+                evm.gas_recorder.set_gas_usage_to_max();
+                return Ok(ExecutionResult::Error(ExecutionError::InvalidMemoryAccess));
+            }
+        }
+    }
+    return_if_gas_too_high!(evm.gas_recorder);
+    runtime.mark_hot(address)?;
+    evm.last_return_data = Memory::new();
+
+    let available_gas = evm.gas_recorder.gas_available() as u64;
+    let gas = available_gas.saturating_sub((available_gas / 64).max(G_EXTCALL_MIN_RETAINED));
+    if gas < G_EXTCALL_MIN_CALLEE || runtime.balance(evm.contract_address)? < value {
+        push!(evm, U256::one());
+        return Ok(ExecutionResult::InProgress);
+    }
+    if !is_precompile(&address, &evm.config) && runtime.code_size(address)?.is_zero() {
+        // Nothing to run, only the value is transferred
+        if !value.is_zero() {
+            runtime.withdrawal(evm.contract_address, value)?;
+            runtime.deposit(address, value)?;
+        }
+        push!(evm, ZERO);
+        return Ok(ExecutionResult::InProgress);
     }
+    let result = execute_call(
+        evm,
+        runtime,
+        debug,
+        CallArgs {
+            gas: gas,
+            code_address: address,
+            contract_address: address,
+            caller_address: evm.contract_address,
+            value: value,
+            args_offset: args_offset,
+            args_size: args_size,
+            ret_offset: 0,
+            ret_size: 0,
+        },
+        None,
+        evm.is_static
+    )?;
+    push!(
+        evm,
+        U256::from(match result {
+            ExecutionResult::Success(_) => 0,
+            ExecutionResult::Error(ExecutionError::Revert(_)) => 1,
+            _ => 2,
+        })
+    );
+    Ok(ExecutionResult::InProgress)
 }
 
 pub struct CallArgs {
     pub gas: u64,
-    pub code_address: U256,
-    pub contract_address: U256,
-    pub caller_address: U256,
+    pub code_address: Address,
+    pub contract_address: Address,
+    pub caller_address: Address,
     pub value: U256,
     pub args_offset: usize,
     pub args_size: usize,
@@ -241,32 +351,75 @@ pub struct CallArgs {
     pub ret_size: usize,
 }
 
+/// Runs a call for the CALL family of opcodes and pushes whether it succeeded
+#[inline]
+pub fn make_call<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool,
+    args: CallArgs,
+    is_static: bool
+) -> Result<ExecutionResult, R::Error> {
+    let execution_result = execute_call(evm, runtime, debug, args, None, is_static)?;
+    push!(
+        evm,
+        U256::from((match execution_result {
+            ExecutionResult::Success(_) => true,
+            _ => false,
+        }) as u64)
+    );
+    Ok(execution_result)
+}
+
+/// Runs a call in a sub context and returns its result, leaving the stack untouched,
+/// `init_code` is run instead of the code at the code address when creating a contract
 #[inline]
-pub fn make_call(
+pub fn execute_call<R: Runtime>(
     evm: &mut EVMContext,
-    runtime: &mut impl Runtime,
+    runtime: &mut R,
     debug: bool,
     mut args: CallArgs,
+    init_code: Option<Vec<u8>>,
     is_static: bool
-) -> ExecutionResult {
+) -> Result<ExecutionResult, R::Error> {
     // Handle precompile case
-    let pre_compile = is_precompile(&args.code_address);
+    let pre_compile = is_precompile(&args.code_address, &evm.config);
     if pre_compile {
         // TODO do better here
         args.contract_address = args.code_address;
     }
     // println!("Address {:x}", args.code_address);
     if !pre_compile {
-        runtime.mark_hot(args.code_address);
+        runtime.mark_hot(args.code_address)?;
     }
     // println!("Making call");
-    let code = match !pre_compile && runtime.exists(args.code_address) {
-        true => runtime.code(args.code_address),
-        false => { vec![0] }
+    let code = match init_code {
+        Some(init_code) => init_code,
+        None => {
+            let code = match !pre_compile && runtime.exists(args.code_address)? {
+                true => runtime.code(args.code_address)?,
+                false => { vec![0] }
+            };
+            // Run the code of the account the code address delegates to (EIP-7702)
+            match delegation_target(evm.config.hardfork, &code) {
+                Some(target) => {
+                    evm.gas_recorder.record_gas_usage(
+                        (DynamicCosts::DelegationAccess {
+                            target_is_cold: !is_precompile(&target, &evm.config) &&
+                            runtime.is_cold(target)?,
+                        }).cost(evm.config.hardfork)
+                    );
+                    return_if_gas_too_high!(evm.gas_recorder);
+                    runtime.mark_hot(target)?;
+                    delegated_code(runtime, &evm.config, target)?
+                }
+                None => code,
+            }
+        }
     };
 
-    if !pre_compile && !runtime.exists(args.code_address) {
-        return ExecutionResult::Success(ExecutionSuccess::RevertedTransaction);
+    if !pre_compile && !runtime.exists(args.code_address)? {
+        return Ok(ExecutionResult::Success(ExecutionSuccess::RevertedTransaction));
     }
     // println!("Gas reamining {:x}", evm.gas_input - evm.gas_recorder.gas_usage as u64);
     if args.args_offset + args.args_size > evm.memory.len() {
@@ -280,13 +433,20 @@ pub fn make_call(
         );
     }
 
-    let gas = args.gas.min(
-        ((evm.gas_input - (evm.gas_recorder.gas_usage.clone() as u64)) * 63).div_ceil(64)
-    );
+    let available_gas = evm.gas_input - (evm.gas_recorder.gas_usage.clone() as u64);
+    // Before EIP-150 the requested gas has to be available rather than being capped at 63/64
+    let gas = if evm.config.hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        args.gas.min((available_gas * 63).div_ceil(64))
+    } else if args.gas > available_gas {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return Ok(ExecutionResult::Error(ExecutionError::InsufficientGas));
+    } else {
+        args.gas
+    };
 
     if args.args_offset.checked_add(args.args_size).is_none() {
         evm.gas_recorder.record_gas_usage(evm.gas_recorder.gas_input as u64);
-        return ExecutionResult::Error(ExecutionError::InvalidMemSize);
+        return Ok(ExecutionResult::Error(ExecutionError::InvalidMemSize));
     }
     let mut sub_evm = EVMContext::create_sub_context(
         args.contract_address,
@@ -300,22 +460,28 @@ pub fn make_call(
         evm.transaction.clone(),
         evm.gas_price,
         evm.nested_index + 1,
-        is_static
+        is_static,
+        evm.config.clone()
     );
 
-    if runtime.balance(evm.contract_address) < args.value {
+    if runtime.balance(evm.contract_address)? < args.value {
         evm.gas_recorder.record_gas_usage(evm.gas_recorder.gas_input as u64);
-        return ExecutionResult::Error(ExecutionError::InsufficientBalance);
+        return Ok(ExecutionResult::Error(ExecutionError::InsufficientBalance));
     }
-    runtime.add_context();
+    runtime.add_context()?;
     if args.value != ZERO {
-        runtime.withdrawal(evm.contract_address, args.value);
-        runtime.deposit(sub_evm.contract_address, args.value);
+        runtime.withdrawal(evm.contract_address, args.value)?;
+        runtime.deposit(sub_evm.contract_address, args.value)?;
     }
-    let execution_result = sub_evm.execute_program(runtime, debug);
+    // The sub call adds to the accounts created so far, which are dropped again if it fails
+    let created_accounts_before = evm.created_accounts.len();
+    sub_evm.created_accounts = mem::take(&mut evm.created_accounts);
+    let execution_result = sub_evm.execute_program(runtime, debug)?;
+    evm.created_accounts = mem::take(&mut sub_evm.created_accounts);
     match &execution_result {
         ExecutionResult::Error(error) => {
-            runtime.revert_context();
+            runtime.revert_context()?;
+            evm.created_accounts.truncate(created_accounts_before);
             match &error {
                 ExecutionError::Revert(result) => {
                     handle_return_data(evm, result, args.ret_offset, args.ret_size);
@@ -326,7 +492,8 @@ pub fn make_call(
             }
         }
         ExecutionResult::Success(success) => {
-            runtime.merge_context();
+            runtime.merge_context()?;
+            evm.logs.append(&mut sub_evm.logs);
             match success {
                 ExecutionSuccess::Return(result) => {
                     handle_return_data(evm, result, args.ret_offset, args.ret_size);
@@ -341,16 +508,9 @@ pub fn make_call(
         }
     }
     evm.gas_recorder.merge(&sub_evm.gas_recorder, &execution_result);
-    push!(
-        evm,
-        U256::from((match execution_result {
-            ExecutionResult::Success(_) => true,
-            _ => false,
-        }) as u64)
-    );
     if evm.gas_recorder.gas_input > evm.gas_recorder.gas_usage {
     }
-    execution_result
+    Ok(execution_result)
 }
 
 fn handle_return_data(
@@ -1,43 +1,62 @@
-use primitive_types::{H160, H256, U256};
+use primitive_types::{H256, U256};
 use sha3::{Digest, Keccak256};
 
-use crate::result::{ExecutionError, ExecutionResult};
+use crate::result::{ExecutionError, ExecutionResult, ExecutionSuccess};
 use crate::{
-    configs::gas_costs::DynamicCosts,
+    configs::{
+        bytecode_spec::{ MAX_CODE_SIZE, MAX_INITCODE_SIZE, RESERVED_CODE_PREFIX },
+        evm_config::EVMConfig,
+        gas_costs::DynamicCosts,
+        hardfork::Hardfork,
+    },
     evm_logic::{
-        evm::{call::CallArgs, macros::return_if_error_in_tuple},
-        util::{h256_to_u256, keccak256, u256_to_array, u256_to_h256, ZERO},
+        evm::{call::CallArgs, macros::{push, return_if_error_in_tuple, return_if_gas_too_high}},
+        eof,
+        gas_recorder::GasRecorder,
+        state::memory::Memory,
+        util::{address_to_u256, keccak256, u256_to_array, u256_to_h256, ZERO},
     },
-    runtime::{self, Runtime},
+    runtime::{self, Address, Runtime},
 };
 
 use super::{
-    call::{self, make_call},
+    call::{self, execute_call},
     macros::{pop, pop_u64, return_if_error},
     EVMContext,
 };
 
-pub fn create_1(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -> ExecutionResult {
+pub fn create_1<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool
+) -> Result<ExecutionResult, R::Error> {
     let (value, offset, size) = (pop!(evm), pop_u64!(evm) as usize, pop_u64!(evm) as usize);
-    let sender_address = evm.message.caller;
-    let sender_nonce = runtime.nonce(sender_address);
-    let mut stream = rlp::RlpStream::new_list(2);
-    stream.append(&sender_address);
-    stream.append(&sender_nonce);
-    let address: H160 = H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into();
+    let address = create_address(evm.contract_address, runtime.nonce(evm.contract_address)?);
+    evm.gas_recorder.record_gas_usage(DynamicCosts::Create.cost(evm.config.hardfork));
     create(
         evm,
-        h256_to_u256(H256::from(address)),
+        address,
         runtime,
         debug,
         value,
         offset,
         size,
-        true
     )
 }
 
-pub fn create_2(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -> ExecutionResult {
+/// Address of a contract created by `sender` with the given nonce, (CREATE and creation transactions)
+pub fn create_address(sender: Address, nonce: U256) -> Address {
+    let mut stream = rlp::RlpStream::new_list(2);
+    stream.append(&sender);
+    stream.append(&nonce);
+    H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
+}
+
+pub fn create_2<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R,
+    debug: bool
+) -> Result<ExecutionResult, R::Error> {
     let (value, offset, size, salt) = (
         pop!(evm),
         pop_u64!(evm) as usize,
@@ -47,79 +66,167 @@ pub fn create_2(evm: &mut EVMContext, runtime: &mut impl Runtime, debug: bool) -
     let code =
         return_if_error_in_tuple!(evm.memory.read_bytes(offset, size, &mut evm.gas_recorder));
     let code_hash = keccak256(&code);
-    let address: H160 = {
+    let address: Address = {
         let mut hasher = Keccak256::new();
         hasher.update([0xff]);
-        hasher.update(&H160::from(u256_to_h256(evm.message.caller))[..]);
+        hasher.update(&evm.contract_address[..]);
         hasher.update(&u256_to_h256(salt)[..]);
         hasher.update(&code_hash[..]);
         H256::from_slice(hasher.finalize().as_slice()).into()
     };
+    evm.gas_recorder.record_gas_usage((DynamicCosts::Create2 { len: size }).cost(evm.config.hardfork));
     create(
         evm,
-        h256_to_u256(H256::from(address)),
+        address,
         runtime,
         debug,
         value,
         offset,
         size,
-        false
     )
 }
 
-pub fn create(
+pub fn create<R: Runtime>(
     evm: &mut EVMContext,
-    address: U256,
-    runtime: &mut impl Runtime,
+    address: Address,
+    runtime: &mut R,
     debug: bool,
     value: U256,
     offset: usize,
     size: usize,
-    leave_code_on_no_return: bool,
-) -> ExecutionResult {
+) -> Result<ExecutionResult, R::Error> {
     let code =
         return_if_error_in_tuple!(evm.memory.read_bytes(offset, size, &mut evm.gas_recorder));
-    // TODO this contract should be temporary and contain zero code for now
-    runtime.create_contract(address, code);
-    let result = make_call(
+    if evm.config.hardfork.is_enabled(Hardfork::Shanghai) {
+        if size > MAX_INITCODE_SIZE {
+            evm.gas_recorder.set_gas_usage_to_max();
+            return Ok(ExecutionResult::Error(ExecutionError::InitCodeSizeExceeded));
+        }
+        evm.gas_recorder.record_gas_usage(
+            (DynamicCosts::InitCode { size: size }).cost(evm.config.hardfork)
+        );
+    }
+    return_if_gas_too_high!(evm.gas_recorder);
+    evm.last_return_data = Memory::new();
+    if runtime.balance(evm.contract_address)? < value {
+        push!(evm, ZERO);
+        return Ok(ExecutionResult::InProgress);
+    }
+    runtime.increase_nonce(evm.contract_address)?;
+
+    // Everything done by the init code and the deployment is reverted if the deployment fails
+    runtime.add_context()?;
+    // The account has no code until the init code returns it
+    runtime.create_contract(address, vec![])?;
+    if evm.config.hardfork.is_enabled(Hardfork::SpuriousDragon) {
+        runtime.increase_nonce(address)?;
+    }
+    // Refunds and accounts created by the init code are discarded with the rest of its state
+    let gas_refunds_before = evm.gas_recorder.gas_refunds;
+    let created_accounts_before = evm.created_accounts.len();
+    evm.created_accounts.push(address);
+    let available_gas = evm.gas_recorder.gas_available() as u64;
+    // All the remaining gas is passed on before EIP-150
+    let gas = if evm.config.hardfork.is_enabled(Hardfork::TangerineWhistle) {
+        available_gas - available_gas / 64
+    } else {
+        available_gas
+    };
+    let gas_usage_before = evm.gas_recorder.gas_usage;
+    let logs_before = evm.logs.len();
+    let result = execute_call(
         evm,
         runtime,
         debug,
         CallArgs {
-            gas: evm.gas_input - evm.gas_recorder.gas_usage as u64,
+            gas: gas,
             contract_address: address,
             code_address: address,
-            caller_address: evm.message.caller,
+            caller_address: evm.contract_address,
             value: value,
             args_offset: 0,
             args_size: 0,
             ret_offset: 0,
             ret_size: 0,
         },
+        Some(code),
         false,
-    );
-    runtime.increase_nonce(evm.message.caller);
-    runtime.increase_nonce(address);
-    let deployed_code_size;
-    match result.return_result() {
-        Some(result) => {
-            runtime.set_contract_code(address, result.clone());
-            deployed_code_size = runtime.code_size(address).as_usize();
+    )?;
+    let deployed_code = match result {
+        ExecutionResult::Success(ExecutionSuccess::Return(code)) => Some(code),
+        ExecutionResult::Success(_) => Some(vec![]),
+        _ => None,
+    };
+    let deployed_code = match deployed_code {
+        Some(code) => {
+            evm.last_return_data = Memory::new();
+            // The code deposit is paid from the gas left over by the init code
+            let mut deposit_gas_recorder = GasRecorder {
+                gas_input: (gas as usize).saturating_sub(evm.gas_recorder.gas_usage - gas_usage_before),
+                gas_usage: 0,
+                gas_refunds: 0,
+            };
+            match deposit_code(&evm.config, &mut deposit_gas_recorder, code) {
+                Ok(code) => {
+                    evm.gas_recorder.record_gas_usage(deposit_gas_recorder.gas_usage as u64);
+                    Some(code)
+                }
+                Err(_) => {
+                    evm.gas_recorder.record_gas_usage(deposit_gas_recorder.gas_input as u64);
+                    None
+                }
+            }
         }
-        None => {
-            if !leave_code_on_no_return {
-                deployed_code_size = runtime.code_size(address).as_usize();
-            } else {
-                deployed_code_size = 0;
+        None => None,
+    };
+    match deployed_code {
+        Some(code) => {
+            // The init code may have self destructed
+            if runtime.exists(address)? {
+                runtime.set_contract_code(address, code)?;
             }
-            runtime.set_contract_code(address, vec![]);
+            runtime.merge_context()?;
+            push!(evm, address_to_u256(address));
+        }
+        None => {
+            runtime.revert_context()?;
+            evm.gas_recorder.gas_refunds = gas_refunds_before;
+            evm.created_accounts.truncate(created_accounts_before);
+            evm.logs.truncate(logs_before);
+            push!(evm, ZERO);
         }
     }
-    evm.gas_recorder.record_gas_usage(
-        DynamicCosts::Create {
-            deployed_code_size: deployed_code_size,
+    return_if_gas_too_high!(evm.gas_recorder);
+    Ok(ExecutionResult::InProgress)
+}
+
+/// Checks the code returned by init code can be deployed and charges the code deposit to
+/// `gas_recorder`, returns the code to store or the error that makes the deployment fail
+pub fn deposit_code(
+    config: &EVMConfig,
+    gas_recorder: &mut GasRecorder,
+    code: Vec<u8>,
+) -> Result<Vec<u8>, ExecutionError> {
+    let hardfork = config.hardfork;
+    if hardfork.is_enabled(Hardfork::SpuriousDragon) && code.len() > MAX_CODE_SIZE {
+        return Err(ExecutionError::CodeSizeExceeded);
+    }
+    if config.eof && eof::is_eof(&code) {
+        // Invalid containers can't be deployed
+        if eof::validate(&code).is_err() {
+            return Err(ExecutionError::InvalidEofContainer);
         }
-        .cost(),
-    );
-    ExecutionResult::InProgress
+    } else if hardfork.is_enabled(Hardfork::London) && code.first() == Some(&RESERVED_CODE_PREFIX) {
+        return Err(ExecutionError::InvalidCodePrefix);
+    }
+    let cost = (DynamicCosts::CodeDeposit { size: code.len() }).cost(hardfork);
+    if gas_recorder.gas_available() < cost as usize {
+        // In Frontier the account is created without code instead of failing
+        if !hardfork.is_enabled(Hardfork::Homestead) {
+            return Ok(vec![]);
+        }
+        return Err(ExecutionError::InsufficientGas);
+    }
+    gas_recorder.record_gas_usage(cost);
+    Ok(code)
 }
@@ -1,9 +1,12 @@
-use crate::configs::bytecode_spec::opcodes;
-use crate::configs::gas_costs::{ static_costs, DynamicCosts };
-use crate::evm_logic::evm::call::{ call, call_code, delegate_call, static_call };
+use crate::configs::bytecode_spec::{ self, opcodes };
+use crate::configs::gas_costs::{ blob_base_fee, static_costs, DynamicCosts };
+use crate::configs::hardfork::Hardfork;
+use crate::evm_logic::eof;
+use crate::evm_logic::evm::call::{ call, call_code, delegate_call, ext_call, static_call };
 use crate::evm_logic::evm::create::{ create_1, create_2 };
 use crate::evm_logic::evm::macros::{
     pop,
+    pop_address,
     pop_u64,
     pop_usize,
     push,
@@ -12,10 +15,10 @@ use crate::evm_logic::evm::macros::{
     return_if_error_in_tuple,
     return_if_gas_too_high,
 };
-use crate::evm_logic::evm::EVMContext;
+use crate::evm_logic::evm::{ EVMContext, Log };
 use crate::evm_logic::state::memory::Memory;
 use crate::evm_logic::util::{
-    self,
+    address_to_u256,
     h256_to_u256,
     int256_to_uint256,
     keccak256,
@@ -31,17 +34,17 @@ use crate::runtime::Runtime;
 use crate::util::u512_to_u256_checked;
 
 use num256::Uint256;
-use primitive_types::{ U256, U512 };
+use primitive_types::{ H256, U256, U512 };
 use std::ops::{ Not, Rem, Shl, Shr };
 use std::u64;
 
 #[inline]
-pub fn decode_instruction(
+pub fn decode_instruction<R: Runtime>(
     evm: &mut EVMContext,
-    runtime: &mut impl Runtime,
+    runtime: &mut R,
     jump_dests: &[usize],
     debug: bool
-) -> ExecutionResult {
+) -> Result<ExecutionResult, R::Error> {
     /*
     Run the next instruction, adjusting gas usage
     Return the execution state
@@ -49,7 +52,7 @@ pub fn decode_instruction(
 
     // If reached end of code then return success
     if evm.program_counter > evm.program.len() - 1 {
-        return ExecutionResult::Success(ExecutionSuccess::Stop);
+        return Ok(ExecutionResult::Success(ExecutionSuccess::Stop));
     }
 
     // Get the next opcode
@@ -67,13 +70,23 @@ pub fn decode_instruction(
         );
     }
 
+    // Opcodes introduced in a later hardfork are treated as invalid instructions
+    if !bytecode_spec::is_enabled(opcode, evm.config.hardfork) {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return Ok(ExecutionResult::Error(ExecutionError::InvalidOpcode(opcode)));
+    }
+    if bytecode_spec::is_eof_only(opcode) && evm.program.eof.is_none() {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return Ok(ExecutionResult::Error(ExecutionError::InvalidOpcode(opcode)));
+    }
+
     // Flag to not increment the program counter and the end of the loop (due to a jump)
     let mut jump_flag = false;
 
     match opcode {
         // Stop Opcode results in successful exection
         opcodes::STOP => {
-            return ExecutionResult::Success(ExecutionSuccess::Stop);
+            return Ok(ExecutionResult::Success(ExecutionSuccess::Stop));
         }
 
         opcodes::ADD => {
@@ -209,7 +222,7 @@ pub fn decode_instruction(
         opcodes::EXP => {
             let (a, exponent) = (pop!(evm), pop!(evm));
             push!(evm, a.overflowing_pow(exponent).0);
-            evm.gas_recorder.record_gas_usage((DynamicCosts::Exp { power: exponent }).cost());
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Exp { power: exponent }).cost(evm.config.hardfork));
         }
 
         opcodes::SIGNEXTEND => {
@@ -347,7 +360,7 @@ pub fn decode_instruction(
         opcodes::KECCAK256 => {
             // TODO ordering of gas usage
             let (offset, length) = (pop_u64!(evm), pop_u64!(evm));
-            evm.gas_recorder.record_gas_usage((DynamicCosts::Keccak256 { len: length }).cost());
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Keccak256 { len: length }).cost(evm.config.hardfork));
             return_if_gas_too_high!(evm.gas_recorder);
             let bytes = return_if_error_in_tuple!(
                 evm.memory.read_bytes(offset as usize, length as usize, &mut evm.gas_recorder)
@@ -356,28 +369,28 @@ pub fn decode_instruction(
         }
 
         opcodes::ADDRESS => {
-            push!(evm, evm.contract_address);
+            push!(evm, address_to_u256(evm.contract_address));
             evm.gas_recorder.record_gas_usage(2);
         }
 
         opcodes::BALANCE => {
-            let address = pop!(evm);
-            push!(evm, runtime.balance(address));
+            let address = pop_address!(evm);
+            push!(evm, runtime.balance(address)?);
             evm.gas_recorder.record_gas_usage(
                 (DynamicCosts::Balance {
-                    target_is_cold: runtime.is_cold(address),
-                }).cost()
+                    target_is_cold: runtime.is_cold(address)?,
+                }).cost(evm.config.hardfork)
             );
-            runtime.mark_hot(address);
+            runtime.mark_hot(address)?;
         }
 
         opcodes::ORIGIN => {
-            push!(evm, evm.transaction.origin);
+            push!(evm, address_to_u256(evm.transaction.origin));
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::CALLER => {
-            push!(evm, evm.message.caller);
+            push!(evm, address_to_u256(evm.message.caller));
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
@@ -419,7 +432,7 @@ pub fn decode_instruction(
         opcodes::CALLDATACOPY => {
             // TODO fix
             let (dest_offset, offset, size) = (pop_usize!(evm), pop!(evm), pop_usize!(evm));
-            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost());
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost(evm.config.hardfork));
             return_if_gas_too_high!(evm.gas_recorder);
             return_if_error!(
                 evm.memory.copy_from_bytes(
@@ -439,7 +452,7 @@ pub fn decode_instruction(
 
         opcodes::CODECOPY => {
             let (dest_offset, offset, size) = (pop_usize!(evm), pop!(evm), pop_usize!(evm));
-            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost());
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost(evm.config.hardfork));
 
             return_if_error!(evm.memory.expand(dest_offset + size, Some(&mut evm.gas_recorder)));
             if offset < evm.program.bytes.len().into() {
@@ -465,39 +478,39 @@ pub fn decode_instruction(
         }
 
         opcodes::EXTCODESIZE => {
-            let address = pop!(evm);
-            push!(evm, runtime.code_size(address));
+            let address = pop_address!(evm);
+            push!(evm, runtime.code_size(address)?);
             evm.gas_recorder.record_gas_usage(
                 (DynamicCosts::ExtCodeSize {
-                    target_is_cold: runtime.is_cold(address),
-                }).cost()
+                    target_is_cold: runtime.is_cold(address)?,
+                }).cost(evm.config.hardfork)
             );
-            runtime.mark_hot(address);
+            runtime.mark_hot(address)?;
         }
 
         opcodes::EXTCODECOPY => {
             let (addr, dest_offset, offset, size) = (
-                pop!(evm),
+                pop_address!(evm),
                 pop_usize!(evm),
                 pop_usize!(evm),
                 pop_usize!(evm),
             );
             evm.gas_recorder.record_gas_usage(
                 (DynamicCosts::ExtCodeCopy {
-                    target_is_cold: runtime.is_cold(addr),
+                    target_is_cold: runtime.is_cold(addr)?,
                     size_bytes: size,
-                }).cost()
+                }).cost(evm.config.hardfork)
             );
             return_if_error!(
                 evm.memory.copy_from_bytes(
-                    &mut runtime.code(addr),
+                    &mut runtime.code(addr)?,
                     U256::from(offset),
                     dest_offset,
                     size,
                     &mut evm.gas_recorder
                 )
             );
-            runtime.mark_hot(addr);
+            runtime.mark_hot(addr)?;
         }
 
         opcodes::RETURNDATASIZE => {
@@ -507,11 +520,11 @@ pub fn decode_instruction(
 
         opcodes::RETURNDATACOPY => {
             let (dest_offset, offset, size) = (pop_usize!(evm), pop_usize!(evm), pop_usize!(evm));
-            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost());
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost(evm.config.hardfork));
             return_if_gas_too_high!(evm.gas_recorder);
             if offset + size > evm.last_return_data.len() {
                 evm.gas_recorder.record_gas_usage(evm.gas_input as u64);
-                return ExecutionResult::Error(ExecutionError::InvalidMemoryAccess);
+                return Ok(ExecutionResult::Error(ExecutionError::InvalidMemoryAccess));
             }
             return_if_error!(
                 evm.memory.copy_from(
@@ -525,60 +538,91 @@ pub fn decode_instruction(
         }
 
         opcodes::EXTCODEHASH => {
-            let address = pop!(evm);
+            let address = pop_address!(evm);
             evm.gas_recorder.record_gas_usage(
                 (DynamicCosts::ExtCodeHash {
-                    target_is_cold: runtime.is_cold(address),
-                }).cost()
+                    target_is_cold: runtime.is_cold(address)?,
+                }).cost(evm.config.hardfork)
             );
             return_if_gas_too_high!(evm.gas_recorder);
-            push!(evm, U256::from(util::keccak256_u256(address).as_bytes()));
-            runtime.mark_hot(address);
+            // Accounts that don't exist or are empty (EIP-161) have no code hash (EIP-1052)
+            let is_empty =
+                !runtime.exists(address)? ||
+                (runtime.nonce(address)?.is_zero() &&
+                    runtime.balance(address)?.is_zero() &&
+                    runtime.code_size(address)?.is_zero());
+            if is_empty {
+                push!(evm, ZERO);
+            } else {
+                push!(evm, h256_to_u256(runtime.code_hash(address)?));
+            }
+            runtime.mark_hot(address)?;
         }
 
         opcodes::BLOCKHASH => {
             let block_number = pop!(evm);
-            push!(evm, h256_to_u256(runtime.block_hash(block_number)));
+            push!(evm, h256_to_u256(runtime.block_hash(block_number)?));
             evm.gas_recorder.record_gas_usage(static_costs::G_BLOCK_HASH);
         }
 
         opcodes::COINBASE => {
-            push!(evm, runtime.block_coinbase());
+            push!(evm, address_to_u256(runtime.block_coinbase()?));
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::TIMESTAMP => {
-            push!(evm, runtime.block_timestamp());
+            push!(evm, runtime.block_timestamp()?);
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::NUMBER => {
-            push!(evm, runtime.block_number());
+            push!(evm, runtime.block_number()?);
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::DIFFICULTY => {
-            push!(evm, runtime.block_difficulty());
+            // Replaced by PREVRANDAO after the merge (EIP-4399)
+            if evm.config.hardfork.is_enabled(Hardfork::Paris) {
+                push!(evm, runtime.block_randomness()?);
+            } else {
+                push!(evm, runtime.block_difficulty()?);
+            }
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::GASLIMIT => {
-            push!(evm, runtime.block_gas_limit());
+            push!(evm, runtime.block_gas_limit()?);
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::CHAINID => {
-            push!(evm, runtime.chain_id());
+            push!(evm, runtime.chain_id()?);
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
         opcodes::SELFBALANCE => {
-            push!(evm, runtime.balance(evm.contract_address));
+            push!(evm, runtime.balance(evm.contract_address)?);
             evm.gas_recorder.record_gas_usage(static_costs::G_LOW);
         }
 
         opcodes::BASEFEE => {
-            push!(evm, runtime.block_base_fee_per_gas());
+            push!(evm, runtime.block_base_fee_per_gas()?);
+            evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
+        }
+
+        opcodes::BLOBHASH => {
+            let index = pop!(evm);
+            let blob_hashes = &evm.transaction.blob_hashes;
+            if index < U256::from(blob_hashes.len()) {
+                push!(evm, h256_to_u256(blob_hashes[index.as_usize()]));
+            } else {
+                push!(evm, U256::zero());
+            }
+            evm.gas_recorder.record_gas_usage(static_costs::G_VERY_LOW);
+        }
+
+        opcodes::BLOBBASEFEE => {
+            push!(evm, blob_base_fee(runtime.block_excess_blob_gas()?, evm.config.hardfork));
             evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
         }
 
@@ -615,34 +659,42 @@ pub fn decode_instruction(
         }
 
         opcodes::SLOAD => {
-            let key = pop!(evm);
+            let key = u256_to_h256(pop!(evm));
             evm.gas_recorder.record_gas_usage(
                 (DynamicCosts::SLoad {
-                    target_is_cold: runtime.is_cold_index(evm.contract_address, key),
-                }).cost()
+                    target_is_cold: runtime.is_cold_index(evm.contract_address, key)?,
+                }).cost(evm.config.hardfork)
             );
-            runtime.mark_hot_index(evm.contract_address, key);
-            push!(evm, h256_to_u256(runtime.read_storage(evm.contract_address, key)));
+            runtime.mark_hot_index(evm.contract_address, key)?;
+            push!(evm, h256_to_u256(runtime.read_storage(evm.contract_address, key)?));
         }
 
         opcodes::SSTORE => {
             return_error_if_static!(evm);
-            let (key, value) = (pop!(evm), pop!(evm));
+            // Can't be executed with only the call stipend remaining (EIP-2200)
+            if
+                evm.config.hardfork.is_enabled(Hardfork::Istanbul) &&
+                evm.gas_recorder.gas_available() <= (static_costs::G_SSTORE_SENTRY as usize)
+            {
+                evm.gas_recorder.set_gas_usage_to_max();
+                return Ok(ExecutionResult::Error(ExecutionError::InsufficientGas));
+            }
+            let (key, value) = (u256_to_h256(pop!(evm)), pop!(evm));
             let (v_org, v_cur, v_new) = (
-                runtime.read_original_storage(evm.contract_address, key),
-                runtime.read_storage(evm.contract_address, key),
+                runtime.read_original_storage(evm.contract_address, key)?,
+                runtime.read_storage(evm.contract_address, key)?,
                 u256_to_h256(value),
             );
-            runtime.set_storage(evm.contract_address, key, u256_to_h256(value));
+            runtime.set_storage(evm.contract_address, key, u256_to_h256(value))?;
             let dynamic_cost = DynamicCosts::SStore {
                 original: v_org,
                 current: v_cur,
                 new: v_new,
-                target_is_cold: runtime.is_cold_index(evm.contract_address, key),
+                target_is_cold: runtime.is_cold_index(evm.contract_address, key)?,
             };
-            runtime.mark_hot_index(evm.contract_address, key);
-            evm.gas_recorder.record_gas_usage(dynamic_cost.cost());
-            evm.gas_recorder.record_refund(dynamic_cost.refund());
+            runtime.mark_hot_index(evm.contract_address, key)?;
+            evm.gas_recorder.record_gas_usage(dynamic_cost.cost(evm.config.hardfork));
+            evm.gas_recorder.record_refund(dynamic_cost.refund(evm.config.hardfork));
         }
 
         opcodes::JUMP => {
@@ -650,11 +702,11 @@ pub fn decode_instruction(
             // Account for the additional increment in the loop
             if evm.program.bytes.len() - 1 < destination {
                 evm.gas_recorder.set_gas_usage_to_max();
-                return ExecutionResult::Error(ExecutionError::InvalidJump);
+                return Ok(ExecutionResult::Error(ExecutionError::InvalidJump));
             }
             if !jump_dests.contains(&destination) {
                 evm.gas_recorder.set_gas_usage_to_max();
-                return ExecutionResult::Error(ExecutionError::InvalidJump);
+                return Ok(ExecutionResult::Error(ExecutionError::InvalidJump));
             }
             evm.program_counter = destination;
             jump_flag = true;
@@ -666,16 +718,16 @@ pub fn decode_instruction(
             if !condition.eq(&U256::zero()) {
                 if destination > U256::from(u64::MAX) {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::InvalidJump);
+                    return Ok(ExecutionResult::Error(ExecutionError::InvalidJump));
                 }
                 let destination = destination.as_u64() as usize;
                 if evm.program.bytes.len() - 1 < destination {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::InvalidJump);
+                    return Ok(ExecutionResult::Error(ExecutionError::InvalidJump));
                 }
                 if !jump_dests.contains(&destination) {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::InvalidJump);
+                    return Ok(ExecutionResult::Error(ExecutionError::InvalidJump));
                 }
                 evm.program_counter = destination;
                 jump_flag = true;
@@ -703,6 +755,41 @@ pub fn decode_instruction(
             evm.gas_recorder.record_gas_usage(static_costs::G_JUMP_DEST);
         }
 
+        opcodes::TLOAD => {
+            let key = u256_to_h256(pop!(evm));
+            push!(evm, h256_to_u256(runtime.read_transient_storage(evm.contract_address, key)?));
+            evm.gas_recorder.record_gas_usage(static_costs::G_WARM_ACCESS);
+        }
+
+        opcodes::TSTORE => {
+            return_error_if_static!(evm);
+            let (key, value) = (u256_to_h256(pop!(evm)), pop!(evm));
+            runtime.set_transient_storage(evm.contract_address, key, u256_to_h256(value))?;
+            evm.gas_recorder.record_gas_usage(static_costs::G_WARM_ACCESS);
+        }
+
+        opcodes::MCOPY => {
+            let (dest_offset, offset, size) = (pop!(evm), pop!(evm), pop_usize!(evm));
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost(evm.config.hardfork));
+            return_if_gas_too_high!(evm.gas_recorder);
+            // The offsets of an empty copy are ignored, even if they are out of range
+            if size != 0 {
+                if dest_offset > U256::from(u64::MAX) || offset > U256::from(u64::MAX) {
+                    evm.gas_recorder.set_gas_usage_to_max();
+                    return Ok(ExecutionResult::Error(ExecutionError::InsufficientGas));
+                }
+                let (dest_offset, offset) = (dest_offset.as_u64() as usize, offset.as_u64() as usize);
+                return_if_error!(
+                    evm.memory.copy_within(offset, dest_offset, size, &mut evm.gas_recorder)
+                );
+            }
+        }
+
+        opcodes::PUSH_0 => {
+            push!(evm, U256::zero());
+            evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
+        }
+
         opcodes::PUSH_1..=opcodes::PUSH_32 => {
             // Would technically be slightly faster without this (branch for each case) but probably a negligible difference
             let push_number = (opcode - opcodes::PUSH_1 + 1) as usize;
@@ -729,7 +816,7 @@ pub fn decode_instruction(
                 }
                 Err(()) => {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::StackUnderflow);
+                    return Ok(ExecutionResult::Error(ExecutionError::StackUnderflow));
                 }
             }
             evm.gas_recorder.record_gas_usage(static_costs::G_VERY_LOW);
@@ -743,42 +830,40 @@ pub fn decode_instruction(
                 Ok(value) => { value }
                 Err(()) => {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::StackUnderflow);
+                    return Ok(ExecutionResult::Error(ExecutionError::StackUnderflow));
                 }
             };
             let top_value = match top_value {
                 Ok(value) => { value }
                 Err(()) => {
                     evm.gas_recorder.set_gas_usage_to_max();
-                    return ExecutionResult::Error(ExecutionError::StackUnderflow);
+                    return Ok(ExecutionResult::Error(ExecutionError::StackUnderflow));
                 }
             };
             match evm.stack.write_nth(swap_number, top_value) {
                 Ok(_) => {}
                 Err(()) => {
                     // TODO gas
-                    return ExecutionResult::Error(ExecutionError::StackOverflow);
+                    return Ok(ExecutionResult::Error(ExecutionError::StackOverflow));
                 }
             }
             match evm.stack.write_nth(0, bottom_value) {
                 Ok(_) => {}
                 Err(()) => {
                     // TODO gas
-                    return ExecutionResult::Error(ExecutionError::StackOverflow);
+                    return Ok(ExecutionResult::Error(ExecutionError::StackOverflow));
                 }
             }
             evm.gas_recorder.record_gas_usage(static_costs::G_VERY_LOW);
         }
 
-        // TODO log
         opcodes::LOG_0..=opcodes::LOG_4 => {
             return_error_if_static!(evm);
             let log_number = opcode - opcodes::LOG_0;
-            // TODO implement properly
             let (offset, size) = (pop_usize!(evm), pop_usize!(evm));
-            let mut topics: Vec<U256> = Vec::new();
+            let mut topics: Vec<H256> = Vec::new();
             for _ in 0..log_number {
-                topics.push(pop!(evm));
+                topics.push(u256_to_h256(pop!(evm)));
             }
             // TODO refactor this, could do something like refactor memory with a read bytes with gas cost
             let mut log_mem = Memory::new();
@@ -795,22 +880,28 @@ pub fn decode_instruction(
                 (DynamicCosts::Log {
                     topic_length: topics.len() as u8,
                     size: size,
-                }).cost()
+                }).cost(evm.config.hardfork)
             );
+            return_if_gas_too_high!(evm.gas_recorder);
+            evm.logs.push(Log {
+                address: evm.contract_address,
+                topics: topics,
+                data: log_mem.bytes[..size].to_vec(),
+            });
         }
 
         opcodes::CREATE => {
             return_error_if_static!(evm);
-            return_if_error!(create_1(evm, runtime, debug));
+            return_if_error!(create_1(evm, runtime, debug)?);
         }
 
         opcodes::CALL => {
             return_error_if_static!(evm);
-            return_if_error!(call(evm, runtime, debug));
+            return_if_error!(call(evm, runtime, debug)?);
         }
 
         opcodes::CALLCODE => {
-            return_if_error!(call_code(evm, runtime, debug));
+            return_if_error!(call_code(evm, runtime, debug)?);
         }
 
         opcodes::RETURN => {
@@ -825,27 +916,130 @@ pub fn decode_instruction(
                     }
                     None => {
                         evm.gas_recorder.set_gas_usage_to_max();
-                        return ExecutionResult::Error(ExecutionError::InvalidMemoryAccess);
+                        return Ok(ExecutionResult::Error(ExecutionError::InvalidMemoryAccess));
                     }
                 }
             }
             return_if_error!(evm.check_gas_usage());
-            return ExecutionResult::Success(
+            return Ok(ExecutionResult::Success(
                 ExecutionSuccess::Return(evm.memory.to_sub_vec(offset, offset + size))
-            );
+            ));
         }
 
         opcodes::DELEGATECALL => {
-            return_if_error!(delegate_call(evm, runtime, debug));
+            return_if_error!(delegate_call(evm, runtime, debug)?);
         }
 
         opcodes::CREATE2 => {
             return_error_if_static!(evm);
-            return_if_error!(create_2(evm, runtime, debug));
+            return_if_error!(create_2(evm, runtime, debug)?);
         }
 
         opcodes::STATICCALL => {
-            return_if_error!(static_call(evm, runtime, debug));
+            return_if_error!(static_call(evm, runtime, debug)?);
+        }
+
+        opcodes::EXTCALL => {
+            return_if_error!(ext_call(evm, runtime, debug)?);
+        }
+
+        opcodes::DATALOAD => {
+            let offset = pop!(evm);
+            let mut bytes = [0u8; 32];
+            let data = evm.program.eof_data();
+            if offset < U256::from(data.len()) {
+                let offset = offset.as_usize();
+                let end = (offset + 32).min(data.len());
+                bytes[..end - offset].copy_from_slice(&data[offset..end]);
+            }
+            push!(evm, U256::from_big_endian(&bytes));
+            evm.gas_recorder.record_gas_usage(static_costs::G_DATALOAD);
+        }
+
+        opcodes::DATALOADN => {
+            // The offset is checked to be within the data section when the code is validated
+            let offset = eof::read_u16(&evm.program.bytes, evm.program_counter + 1) as usize;
+            let value = U256::from_big_endian(&evm.program.eof_data()[offset..offset + 32]);
+            push!(evm, value);
+            evm.program_counter += 2;
+            evm.gas_recorder.record_gas_usage(static_costs::G_VERY_LOW);
+        }
+
+        opcodes::DATASIZE => {
+            push!(evm, U256::from(evm.program.eof_data().len()));
+            evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
+        }
+
+        opcodes::DATACOPY => {
+            let (dest_offset, offset, size) = (pop_usize!(evm), pop!(evm), pop_usize!(evm));
+            evm.gas_recorder.record_gas_usage((DynamicCosts::Copy { size_bytes: size }).cost(evm.config.hardfork));
+            return_if_gas_too_high!(evm.gas_recorder);
+            return_if_error!(
+                evm.memory.copy_from_bytes(
+                    evm.program.eof_data(),
+                    offset,
+                    dest_offset,
+                    size,
+                    &mut evm.gas_recorder
+                )
+            );
+        }
+
+        opcodes::RJUMP => {
+            let offset = eof::read_i16(&evm.program.bytes, evm.program_counter + 1);
+            evm.program_counter = ((evm.program_counter as isize) + 3 + (offset as isize)) as usize;
+            jump_flag = true;
+            evm.gas_recorder.record_gas_usage(static_costs::G_BASE);
+        }
+
+        opcodes::RJUMPI => {
+            let condition = pop!(evm);
+            let offset = if condition.is_zero() {
+                0
+            } else {
+                eof::read_i16(&evm.program.bytes, evm.program_counter + 1)
+            };
+            evm.program_counter = ((evm.program_counter as isize) + 3 + (offset as isize)) as usize;
+            jump_flag = true;
+            evm.gas_recorder.record_gas_usage(static_costs::G_RJUMPI);
+        }
+
+        opcodes::RJUMPV => {
+            let case = pop!(evm);
+            let max_index = evm.program.bytes[evm.program_counter + 1] as usize;
+            let next_pc = evm.program_counter + 2 + 2 * (max_index + 1);
+            let offset = if case <= U256::from(max_index) {
+                eof::read_i16(&evm.program.bytes, evm.program_counter + 2 + 2 * case.as_usize())
+            } else {
+                0
+            };
+            evm.program_counter = ((next_pc as isize) + (offset as isize)) as usize;
+            jump_flag = true;
+            evm.gas_recorder.record_gas_usage(static_costs::G_RJUMPI);
+        }
+
+        opcodes::CALLF => {
+            let section = eof::read_u16(&evm.program.bytes, evm.program_counter + 1) as usize;
+            let container = evm.program.eof.as_ref().unwrap();
+            let max_stack_increase = container.types[section].max_stack_increase as usize;
+            if
+                evm.stack.stack_pointer + max_stack_increase > 1024 ||
+                evm.return_stack.len() >= 1024
+            {
+                evm.gas_recorder.set_gas_usage_to_max();
+                return Ok(ExecutionResult::Error(ExecutionError::StackOverflow));
+            }
+            evm.return_stack.push(evm.program_counter + 3);
+            evm.program_counter = container.code_sections[section].start;
+            jump_flag = true;
+            evm.gas_recorder.record_gas_usage(static_costs::G_LOW);
+        }
+
+        opcodes::RETF => {
+            // Validation ensures RETF is only used in sections entered with CALLF
+            evm.program_counter = evm.return_stack.pop().unwrap();
+            jump_flag = true;
+            evm.gas_recorder.record_gas_usage(static_costs::G_VERY_LOW);
         }
 
         opcodes::REVERT => {
@@ -858,67 +1052,47 @@ pub fn decode_instruction(
                     }
                     None => {
                         evm.gas_recorder.set_gas_usage_to_max();
-                        return ExecutionResult::Error(ExecutionError::InvalidMemoryAccess);
+                        return Ok(ExecutionResult::Error(ExecutionError::InvalidMemoryAccess));
                     }
                 }
             }
             return_if_error!(evm.check_gas_usage());
-            return ExecutionResult::Error(
+            return Ok(ExecutionResult::Error(
                 ExecutionError::Revert(evm.memory.to_sub_vec(offset, offset + size))
-            );
+            ));
         }
 
         opcodes::SELFDESTRUCT => {
-            let address = pop!(evm);
-            let address_exists = runtime.exists(address);
-            let is_cold = runtime.is_cold(address);
-            let balance = runtime.balance(evm.contract_address);
-            println!("Is cold {:?}", is_cold);
-            println!("Exists {:?}", address_exists);
-            println!("Positive Balance {:?}", balance.is_zero());
-            println!("Receiving Balance {:?}", runtime.balance(address));
-            println!("Address {:?}", address);
-            evm.gas_recorder.record_gas_usage(
-                (DynamicCosts::SelfDestruct {
-                    address_exists: address_exists,
-                    is_cold: is_cold,
-                    positive_balance: !balance.is_zero(),
-                }).cost()
-            );
-            println!(
-                "Gas cost {:?}",
-                (DynamicCosts::SelfDestruct {
-                    address_exists: address_exists,
-                    is_cold: is_cold,
-                    positive_balance: !balance.is_zero(),
-                }).cost()
-            );
-            evm.gas_recorder.record_refund(
-                (DynamicCosts::SelfDestruct {
-                    address_exists: address_exists,
-                    is_cold: is_cold,
-                    positive_balance: !balance.is_zero(),
-                }).refund()
-            );
-            println!(
-                "Gas cost {:?}",
-                (DynamicCosts::SelfDestruct {
-                    address_exists: address_exists,
-                    is_cold: is_cold,
-                    positive_balance: !balance.is_zero(),
-                }).refund()
-            );
-            println!("Self Destruct");
+            return_error_if_static!(evm);
+            let address = pop_address!(evm);
+            let balance = runtime.balance(evm.contract_address)?;
+            let dynamic_cost = DynamicCosts::SelfDestruct {
+                address_exists: runtime.exists(address)?,
+                is_cold: runtime.is_cold(address)?,
+                positive_balance: !balance.is_zero(),
+                already_deleted: runtime.is_deleted(evm.contract_address)?,
+            };
+            evm.gas_recorder.record_gas_usage(dynamic_cost.cost(evm.config.hardfork));
+            evm.gas_recorder.record_refund(dynamic_cost.refund(evm.config.hardfork));
             return_if_error!(evm.check_gas_usage());
-            runtime.withdrawal(evm.contract_address, balance);
-            runtime.deposit(address, balance);
-            runtime.mark_delete(evm.contract_address);
-            println!("Self Destruct");
-            return ExecutionResult::Success(ExecutionSuccess::Stop);
+            runtime.mark_hot(address)?;
+            runtime.withdrawal(evm.contract_address, balance)?;
+            runtime.deposit(address, balance)?;
+            // From Cancun only contracts created in the same transaction are deleted (EIP-6780),
+            // otherwise only the balance is sent
+            if
+                !evm.config.hardfork.is_enabled(Hardfork::Cancun) ||
+                evm.created_accounts.contains(&evm.contract_address)
+            {
+                runtime.mark_delete(evm.contract_address)?;
+            }
+            return Ok(ExecutionResult::Success(ExecutionSuccess::Stop));
         }
 
         _ => {
-            panic!("Opcode not implemented {}", opcode);
+            // Includes INVALID (0xFE)
+            evm.gas_recorder.set_gas_usage_to_max();
+            return Ok(ExecutionResult::Error(ExecutionError::InvalidOpcode(opcode)));
         }
     }
 
@@ -926,7 +1100,7 @@ pub fn decode_instruction(
     if !jump_flag {
         evm.program_counter += 1;
     }
-    return ExecutionResult::InProgress;
+    return Ok(ExecutionResult::InProgress);
 }
 
 #[inline]
@@ -936,6 +1110,10 @@ pub fn calculate_jump_dests(evm: &mut EVMContext) -> Vec<usize> {
     Return the execution state
     */
     let mut jump_dests = vec![];
+    // EOF code has no JUMP or JUMPI
+    if evm.program.eof.is_some() {
+        return jump_dests;
+    }
 
     let mut pc = 0;
 
@@ -947,8 +1125,9 @@ pub fn calculate_jump_dests(evm: &mut EVMContext) -> Vec<usize> {
                 jump_dests.push(pc);
             }
 
-            opcodes::PUSH_1..=opcodes::PUSH_32 => {
-                let push_number = (opcode - opcodes::PUSH_1 + 1) as usize;
+            // PUSH0 has no immediate data so skips nothing
+            opcodes::PUSH_0..=opcodes::PUSH_32 => {
+                let push_number = (opcode - opcodes::PUSH_0) as usize;
                 pc += push_number as usize;
             }
 
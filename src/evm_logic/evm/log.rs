@@ -0,0 +1,12 @@
+use primitive_types::H256;
+
+use crate::runtime::Address;
+
+/// An event emitted by one of the LOG instructions, kept only if the call that emitted it and
+/// all of its callers succeed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
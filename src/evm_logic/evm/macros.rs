@@ -4,7 +4,7 @@ macro_rules! return_if_error {
     ($evm_val:expr) => {
         match $evm_val {
             ExecutionResult::Error(err) => {
-                return ExecutionResult::Error(err)},
+                return ExecutionResult::Error(err).into()},
             _ => {}
         }
     };
@@ -26,7 +26,7 @@ macro_rules! return_if_error_in_tuple {
     ($evm_val:expr) => {
         match $evm_val.0 {
             ExecutionResult::Error(err) => {
-                return ExecutionResult::Error(err)}
+                return ExecutionResult::Error(err).into()}
             _ => {$evm_val.1}
         }
     };
@@ -36,7 +36,7 @@ pub(crate) use return_if_error_in_tuple;
 macro_rules! return_error_if_static {
     ($evm_val:expr) => {
         if $evm_val.is_static {
-            return ExecutionResult::Error(ExecutionError::ModifyStaticState);
+            return ExecutionResult::Error(ExecutionError::ModifyStaticState).into();
         }
 
     };
@@ -50,7 +50,7 @@ macro_rules! pop {
         let result = match result {
             Err(()) => {
                 $evm.gas_recorder.set_gas_usage_to_max();
-                return ExecutionResult::Error(ExecutionError::InsufficientValuesOnStack);
+                return ExecutionResult::Error(ExecutionError::InsufficientValuesOnStack).into();
             }
             Ok(value) => value,
         };
@@ -64,7 +64,7 @@ macro_rules! push {
         let result = $evm.stack.push($value);
         match result {
             Err(()) => {
-                return ExecutionResult::Error(ExecutionError::StackOverflow);
+                return ExecutionResult::Error(ExecutionError::StackOverflow).into();
             }
             _ => {}
         }
@@ -78,7 +78,7 @@ macro_rules! pop_u64 {
         let result = match result {
             Err(()) => {
                 $evm.gas_recorder.set_gas_usage_to_max();
-                return ExecutionResult::Error(ExecutionError::InsufficientValuesOnStack);
+                return ExecutionResult::Error(ExecutionError::InsufficientValuesOnStack).into();
             }
             Ok(value) => value,
         };
@@ -88,7 +88,7 @@ macro_rules! pop_u64 {
             $evm.gas_recorder.set_gas_usage_to_max();
             // $evm.gas_recorder.gas_usage = $evm.gas_input as usize;
             // TODO refactor this away as unclear
-            return ExecutionResult::Error(ExecutionError::InsufficientGas);
+            return ExecutionResult::Error(ExecutionError::InsufficientGas).into();
         }
         result.as_u64()
     }};
@@ -107,8 +107,15 @@ macro_rules! return_if_gas_too_high {
     ($gas_recorder:expr) => {
         if !$gas_recorder.is_valid() {
             $gas_recorder.gas_usage = $gas_recorder.gas_input;
-            return ExecutionResult::Error(ExecutionError::InsufficientGas);
+            return ExecutionResult::Error(ExecutionError::InsufficientGas).into();
         }
     };
 }
 pub(crate) use return_if_gas_too_high;
+
+macro_rules! pop_address {
+    ($evm_val:tt) => {{
+        crate::evm_logic::util::u256_to_address(pop!($evm_val))
+    }};
+}
+pub(crate) use pop_address;
@@ -0,0 +1,360 @@
+use std::ptr;
+
+use blst::{
+    blst_bendian_from_fp,
+    blst_final_exp,
+    blst_fp,
+    blst_fp12,
+    blst_fp12_is_one,
+    blst_fp12_mul,
+    blst_fp12_one,
+    blst_fp2,
+    blst_fp_from_bendian,
+    blst_map_to_g1,
+    blst_map_to_g2,
+    blst_miller_loop,
+    blst_p1,
+    blst_p1_add_or_double,
+    blst_p1_add_or_double_affine,
+    blst_p1_affine,
+    blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf,
+    blst_p1_affine_on_curve,
+    blst_p1_from_affine,
+    blst_p1_mult,
+    blst_p1_to_affine,
+    blst_p2,
+    blst_p2_add_or_double,
+    blst_p2_add_or_double_affine,
+    blst_p2_affine,
+    blst_p2_affine_in_g2,
+    blst_p2_affine_is_inf,
+    blst_p2_affine_on_curve,
+    blst_p2_from_affine,
+    blst_p2_mult,
+    blst_p2_to_affine,
+    blst_scalar,
+    blst_scalar_from_bendian,
+};
+
+use crate::{
+    configs::gas_costs::{ precompile_costs, DynamicPreCompileCosts },
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+// Encodings from EIP-2537, field elements are padded to 64 bytes
+const FP_LENGTH: usize = 48;
+const PADDED_FP_LENGTH: usize = 64;
+const PADDED_FP2_LENGTH: usize = 2 * PADDED_FP_LENGTH;
+const G1_LENGTH: usize = 2 * PADDED_FP_LENGTH;
+const G2_LENGTH: usize = 2 * PADDED_FP2_LENGTH;
+const SCALAR_LENGTH: usize = 32;
+
+/// 0x0b: adds two G1 points
+pub fn g1_add_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(precompile_costs::G_BLS12_G1ADD);
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.len() != 2 * G1_LENGTH {
+        return invalid_input(evm);
+    }
+    // Points only have to be on the curve for addition
+    let (a, b) = match
+        (decode_g1(&input[..G1_LENGTH], false), decode_g1(&input[G1_LENGTH..], false))
+    {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => {
+            return invalid_input(evm);
+        }
+    };
+    let mut a_projective = blst_p1::default();
+    let mut result = blst_p1::default();
+    unsafe {
+        blst_p1_from_affine(&mut a_projective, &a);
+        blst_p1_add_or_double_affine(&mut result, &a_projective, &b);
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g1(&result)))
+}
+
+/// 0x0c: multi scalar multiplication of G1 points
+pub fn g1_msm_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let pair_length = G1_LENGTH + SCALAR_LENGTH;
+    let pairs = evm.message.data.len() / pair_length;
+    evm.gas_recorder.record_gas_usage(
+        (DynamicPreCompileCosts::Bls12G1Msm { pairs: pairs }).cost(evm.config.hardfork)
+    );
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.is_empty() || input.len() % pair_length != 0 {
+        return invalid_input(evm);
+    }
+    let mut result = blst_p1::default();
+    for pair in input.chunks(pair_length) {
+        let point = match decode_g1(&pair[..G1_LENGTH], true) {
+            Ok(point) => point,
+            Err(()) => {
+                return invalid_input(evm);
+            }
+        };
+        let scalar = decode_scalar(&pair[G1_LENGTH..]);
+        let (mut projective, mut product, mut sum) = (
+            blst_p1::default(),
+            blst_p1::default(),
+            blst_p1::default(),
+        );
+        unsafe {
+            blst_p1_from_affine(&mut projective, &point);
+            blst_p1_mult(&mut product, &projective, scalar.b.as_ptr(), SCALAR_LENGTH * 8);
+            blst_p1_add_or_double(&mut sum, &result, &product);
+        }
+        result = sum;
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g1(&result)))
+}
+
+/// 0x0d: adds two G2 points
+pub fn g2_add_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(precompile_costs::G_BLS12_G2ADD);
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.len() != 2 * G2_LENGTH {
+        return invalid_input(evm);
+    }
+    let (a, b) = match
+        (decode_g2(&input[..G2_LENGTH], false), decode_g2(&input[G2_LENGTH..], false))
+    {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => {
+            return invalid_input(evm);
+        }
+    };
+    let mut a_projective = blst_p2::default();
+    let mut result = blst_p2::default();
+    unsafe {
+        blst_p2_from_affine(&mut a_projective, &a);
+        blst_p2_add_or_double_affine(&mut result, &a_projective, &b);
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g2(&result)))
+}
+
+/// 0x0e: multi scalar multiplication of G2 points
+pub fn g2_msm_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let pair_length = G2_LENGTH + SCALAR_LENGTH;
+    let pairs = evm.message.data.len() / pair_length;
+    evm.gas_recorder.record_gas_usage(
+        (DynamicPreCompileCosts::Bls12G2Msm { pairs: pairs }).cost(evm.config.hardfork)
+    );
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.is_empty() || input.len() % pair_length != 0 {
+        return invalid_input(evm);
+    }
+    let mut result = blst_p2::default();
+    for pair in input.chunks(pair_length) {
+        let point = match decode_g2(&pair[..G2_LENGTH], true) {
+            Ok(point) => point,
+            Err(()) => {
+                return invalid_input(evm);
+            }
+        };
+        let scalar = decode_scalar(&pair[G2_LENGTH..]);
+        let (mut projective, mut product, mut sum) = (
+            blst_p2::default(),
+            blst_p2::default(),
+            blst_p2::default(),
+        );
+        unsafe {
+            blst_p2_from_affine(&mut projective, &point);
+            blst_p2_mult(&mut product, &projective, scalar.b.as_ptr(), SCALAR_LENGTH * 8);
+            blst_p2_add_or_double(&mut sum, &result, &product);
+        }
+        result = sum;
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g2(&result)))
+}
+
+/// 0x0f: returns 1 if the product of the pairings of each (G1, G2) pair is one
+pub fn pairing_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let pair_length = G1_LENGTH + G2_LENGTH;
+    let pairs = evm.message.data.len() / pair_length;
+    evm.gas_recorder.record_gas_usage(
+        (DynamicPreCompileCosts::Bls12Pairing { pairs: pairs }).cost(evm.config.hardfork)
+    );
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.is_empty() || input.len() % pair_length != 0 {
+        return invalid_input(evm);
+    }
+    let mut accumulator = unsafe { *blst_fp12_one() };
+    for pair in input.chunks(pair_length) {
+        let (p, q) = match
+            (decode_g1(&pair[..G1_LENGTH], true), decode_g2(&pair[G1_LENGTH..], true))
+        {
+            (Ok(p), Ok(q)) => (p, q),
+            _ => {
+                return invalid_input(evm);
+            }
+        };
+        // Pairings with the point at infinity are one
+        if unsafe { blst_p1_affine_is_inf(&p) || blst_p2_affine_is_inf(&q) } {
+            continue;
+        }
+        let (mut miller_loop, mut product) = (blst_fp12::default(), blst_fp12::default());
+        unsafe {
+            blst_miller_loop(&mut miller_loop, &q, &p);
+            blst_fp12_mul(&mut product, &accumulator, &miller_loop);
+        }
+        accumulator = product;
+    }
+    let mut result = blst_fp12::default();
+    let is_one = unsafe {
+        blst_final_exp(&mut result, &accumulator);
+        blst_fp12_is_one(&result)
+    };
+    let mut output = vec![0u8; 32];
+    output[31] = is_one as u8;
+    ExecutionResult::Success(ExecutionSuccess::Return(output))
+}
+
+/// 0x10: maps a field element to a G1 point
+pub fn map_fp_to_g1_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(precompile_costs::G_BLS12_MAP_FP_TO_G1);
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.len() != PADDED_FP_LENGTH {
+        return invalid_input(evm);
+    }
+    let fp = match decode_fp(&input) {
+        Ok(fp) => fp,
+        Err(()) => {
+            return invalid_input(evm);
+        }
+    };
+    let mut result = blst_p1::default();
+    unsafe {
+        blst_map_to_g1(&mut result, &fp, ptr::null());
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g1(&result)))
+}
+
+/// 0x11: maps an element of the quadratic extension field to a G2 point
+pub fn map_fp2_to_g2_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(precompile_costs::G_BLS12_MAP_FP2_TO_G2);
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = evm.message.data.clone();
+    if input.len() != PADDED_FP2_LENGTH {
+        return invalid_input(evm);
+    }
+    let fp2 = match decode_fp2(&input) {
+        Ok(fp2) => fp2,
+        Err(()) => {
+            return invalid_input(evm);
+        }
+    };
+    let mut result = blst_p2::default();
+    unsafe {
+        blst_map_to_g2(&mut result, &fp2, ptr::null());
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g2(&result)))
+}
+
+/// Invalid input consumes all the gas given to the precompile
+fn invalid_input(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.set_gas_usage_to_max();
+    ExecutionResult::Error(ExecutionError::InvalidPrecompileInput)
+}
+
+/// Decodes a padded big endian field element, which must be smaller than the modulus
+fn decode_fp(input: &[u8]) -> Result<blst_fp, ()> {
+    let (padding, bytes) = input.split_at(PADDED_FP_LENGTH - FP_LENGTH);
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(());
+    }
+    let mut fp = blst_fp::default();
+    let mut canonical = [0u8; FP_LENGTH];
+    unsafe {
+        blst_fp_from_bendian(&mut fp, bytes.as_ptr());
+        blst_bendian_from_fp(canonical.as_mut_ptr(), &fp);
+    }
+    // Values above the modulus are reduced when converted, so don't round trip
+    if canonical != bytes {
+        return Err(());
+    }
+    Ok(fp)
+}
+
+fn decode_fp2(input: &[u8]) -> Result<blst_fp2, ()> {
+    Ok(blst_fp2 {
+        fp: [decode_fp(&input[..PADDED_FP_LENGTH])?, decode_fp(&input[PADDED_FP_LENGTH..])?],
+    })
+}
+
+fn decode_g1(input: &[u8], subgroup_check: bool) -> Result<blst_p1_affine, ()> {
+    let point = blst_p1_affine {
+        x: decode_fp(&input[..PADDED_FP_LENGTH])?,
+        y: decode_fp(&input[PADDED_FP_LENGTH..])?,
+    };
+    // The point at infinity is encoded as zeros and passes both checks
+    if !unsafe { blst_p1_affine_on_curve(&point) } {
+        return Err(());
+    }
+    if subgroup_check && !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(());
+    }
+    Ok(point)
+}
+
+fn decode_g2(input: &[u8], subgroup_check: bool) -> Result<blst_p2_affine, ()> {
+    let point = blst_p2_affine {
+        x: decode_fp2(&input[..PADDED_FP2_LENGTH])?,
+        y: decode_fp2(&input[PADDED_FP2_LENGTH..])?,
+    };
+    if !unsafe { blst_p2_affine_on_curve(&point) } {
+        return Err(());
+    }
+    if subgroup_check && !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(());
+    }
+    Ok(point)
+}
+
+/// Scalars don't have to be reduced by the group order
+fn decode_scalar(input: &[u8]) -> blst_scalar {
+    let mut scalar = blst_scalar::default();
+    unsafe {
+        blst_scalar_from_bendian(&mut scalar, input.as_ptr());
+    }
+    scalar
+}
+
+fn encode_fp(fp: &blst_fp, output: &mut [u8]) {
+    unsafe {
+        blst_bendian_from_fp(output[PADDED_FP_LENGTH - FP_LENGTH..].as_mut_ptr(), fp);
+    }
+}
+
+fn encode_g1(point: &blst_p1) -> Vec<u8> {
+    let mut affine = blst_p1_affine::default();
+    unsafe {
+        blst_p1_to_affine(&mut affine, point);
+    }
+    let mut output = vec![0u8; G1_LENGTH];
+    encode_fp(&affine.x, &mut output[..PADDED_FP_LENGTH]);
+    encode_fp(&affine.y, &mut output[PADDED_FP_LENGTH..]);
+    output
+}
+
+fn encode_g2(point: &blst_p2) -> Vec<u8> {
+    let mut affine = blst_p2_affine::default();
+    unsafe {
+        blst_p2_to_affine(&mut affine, point);
+    }
+    let mut output = vec![0u8; G2_LENGTH];
+    for (i, fp) in [affine.x.fp[0], affine.x.fp[1], affine.y.fp[0], affine.y.fp[1]]
+        .iter()
+        .enumerate() {
+        encode_fp(fp, &mut output[i * PADDED_FP_LENGTH..(i + 1) * PADDED_FP_LENGTH]);
+    }
+    output
+}
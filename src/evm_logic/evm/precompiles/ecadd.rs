@@ -0,0 +1,65 @@
+use bn::{ AffineG1, Fq, Group, G1 };
+
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+// Encodings from EIP-196, points are the big endian x and y coordinates
+pub(super) const FQ_LENGTH: usize = 32;
+pub(super) const G1_LENGTH: usize = 2 * FQ_LENGTH;
+
+/// 0x06: adds two alt_bn128 G1 points
+pub fn ecadd_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(DynamicPreCompileCosts::EcAdd.cost(evm.config.hardfork));
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = padded_input(&evm.message.data, 2 * G1_LENGTH);
+    let (a, b) = match (decode_g1(&input[..G1_LENGTH]), decode_g1(&input[G1_LENGTH..])) {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => {
+            return invalid_input(evm);
+        }
+    };
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g1(a + b)))
+}
+
+/// Missing input bytes are treated as zero and extra bytes are ignored
+pub(super) fn padded_input(data: &[u8], length: usize) -> Vec<u8> {
+    let mut input = vec![0u8; length];
+    let copied = data.len().min(length);
+    input[..copied].copy_from_slice(&data[..copied]);
+    input
+}
+
+pub(super) fn invalid_input(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.set_gas_usage_to_max();
+    ExecutionResult::Error(ExecutionError::InvalidPrecompileInput)
+}
+
+/// Decodes a field element, which must be smaller than the modulus
+pub(super) fn decode_fq(input: &[u8]) -> Result<Fq, ()> {
+    Fq::from_slice(input).map_err(|_| ())
+}
+
+/// Decodes a point that must be on the curve, (0, 0) is the point at infinity
+pub(super) fn decode_g1(input: &[u8]) -> Result<G1, ()> {
+    let x = decode_fq(&input[..FQ_LENGTH])?;
+    let y = decode_fq(&input[FQ_LENGTH..G1_LENGTH])?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1::zero());
+    }
+    AffineG1::new(x, y)
+        .map(Into::into)
+        .map_err(|_| ())
+}
+
+/// The point at infinity is encoded as (0, 0)
+pub(super) fn encode_g1(point: G1) -> Vec<u8> {
+    let mut output = vec![0u8; G1_LENGTH];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut output[..FQ_LENGTH]).unwrap();
+        point.y().to_big_endian(&mut output[FQ_LENGTH..]).unwrap();
+    }
+    output
+}
@@ -0,0 +1,26 @@
+use bn::Fr;
+
+use super::ecadd::{ decode_g1, encode_g1, invalid_input, padded_input, G1_LENGTH };
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+const SCALAR_LENGTH: usize = 32;
+
+/// 0x07: multiplies an alt_bn128 G1 point by a scalar
+pub fn ecmul_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(DynamicPreCompileCosts::EcMul.cost(evm.config.hardfork));
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = padded_input(&evm.message.data, G1_LENGTH + SCALAR_LENGTH);
+    let point = match decode_g1(&input[..G1_LENGTH]) {
+        Ok(point) => point,
+        Err(_) => {
+            return invalid_input(evm);
+        }
+    };
+    // Scalars are reduced modulo the group order, so any 32 bytes are valid
+    let scalar = Fr::from_slice(&input[G1_LENGTH..]).unwrap();
+    ExecutionResult::Success(ExecutionSuccess::Return(encode_g1(point * scalar)))
+}
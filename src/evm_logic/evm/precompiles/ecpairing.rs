@@ -0,0 +1,58 @@
+use bn::{ pairing_batch, AffineG2, Fq2, Group, Gt, G2 };
+
+use super::ecadd::{ decode_fq, decode_g1, invalid_input, FQ_LENGTH, G1_LENGTH };
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+const G2_LENGTH: usize = 4 * FQ_LENGTH;
+const PAIR_LENGTH: usize = G1_LENGTH + G2_LENGTH;
+
+/// 0x08: checks that the product of the pairings of each (G1, G2) pair is one,
+/// returns 1 if it is and 0 otherwise
+pub fn ecpairing_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let input = evm.message.data.clone();
+    let cost = (DynamicPreCompileCosts::EcPairing {
+        pairs: input.len() / PAIR_LENGTH,
+    }).cost(evm.config.hardfork);
+    evm.gas_recorder.record_gas_usage(cost);
+    return_if_gas_too_high!(evm.gas_recorder);
+    if input.len() % PAIR_LENGTH != 0 {
+        return invalid_input(evm);
+    }
+    let mut pairs = vec![];
+    for pair in input.chunks(PAIR_LENGTH) {
+        match (decode_g1(&pair[..G1_LENGTH]), decode_g2(&pair[G1_LENGTH..])) {
+            (Ok(a), Ok(b)) => pairs.push((a, b)),
+            _ => {
+                return invalid_input(evm);
+            }
+        }
+    }
+    let mut result = vec![0u8; 32];
+    if pairing_batch(&pairs) == Gt::one() {
+        result[31] = 1;
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(result))
+}
+
+/// Decodes a point that must be on the curve and in the subgroup, (0, 0) is the point at infinity.
+/// Each coordinate is encoded with the imaginary part first.
+fn decode_g2(input: &[u8]) -> Result<G2, ()> {
+    let x = Fq2::new(
+        decode_fq(&input[FQ_LENGTH..2 * FQ_LENGTH])?,
+        decode_fq(&input[..FQ_LENGTH])?
+    );
+    let y = Fq2::new(
+        decode_fq(&input[3 * FQ_LENGTH..G2_LENGTH])?,
+        decode_fq(&input[2 * FQ_LENGTH..3 * FQ_LENGTH])?
+    );
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2::zero());
+    }
+    AffineG2::new(x, y)
+        .map(Into::into)
+        .map_err(|_| ())
+}
@@ -34,7 +34,7 @@ pub fn ecrecover_contract(evm: &mut EVMContext) -> ExecutionResult {
     return ExecutionResult::Success(ExecutionSuccess::Return(result.to_vec()));
 }
 
-fn ecrecover(hash: U256, v: U256, r: U256, s: U256) -> U256 {
+pub(crate) fn ecrecover(hash: U256, v: U256, r: U256, s: U256) -> U256 {
     let mut v = v.clone();
     // 1) Convert 'hash' to 32-byte array
     let mut msg_bytes = [0u8; 32];
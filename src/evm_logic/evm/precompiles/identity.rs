@@ -0,0 +1,15 @@
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+/// Returns the input unchanged, used to copy memory
+pub fn identity_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let cost = (DynamicPreCompileCosts::Identity {
+        data_word_size: evm.message.data.len().div_ceil(32),
+    }).cost(evm.config.hardfork);
+    evm.gas_recorder.record_gas_usage(cost);
+    return_if_gas_too_high!(evm.gas_recorder);
+    ExecutionResult::Success(ExecutionSuccess::Return(evm.message.data.clone()))
+}
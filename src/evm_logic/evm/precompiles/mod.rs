@@ -1,2 +1,287 @@
+pub mod bls12_381;
+pub mod ecadd;
+pub mod ecmul;
+pub mod ecpairing;
 pub mod ecrecover;
+pub mod identity;
+pub mod modexp;
+pub mod p256_verify;
+pub mod ripemd_160;
 pub mod sha2_256;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use primitive_types::{ H256, U256 };
+
+use crate::configs::evm_config::EVMConfig;
+use crate::configs::precompiles::{ self as precompile_addresses, is_precompile };
+use crate::result::{ ExecutionError, ExecutionResult };
+use crate::runtime::{ Address, Runtime, StorageKey, StorageValue };
+
+use super::EVMContext;
+
+/// A contract implemented natively rather than in EVM code, called like any other contract
+pub trait Precompile: Send + Sync {
+    /// Changes made through the runtime are reverted if an error is returned, a RuntimeError
+    /// should be returned as is
+    fn execute(
+        &self,
+        context: &mut PrecompileContext,
+        runtime: &mut dyn Runtime<Error = RuntimeError>
+    ) -> Result<ExecutionResult, RuntimeError>;
+}
+
+/// The host runtime failed while a precompile was using it, the runtime's own error is returned
+/// from execute_transaction
+#[derive(Debug, PartialEq, Eq)]
+pub struct RuntimeError(());
+
+/// The call a precompile is executing
+pub struct PrecompileContext<'a> {
+    evm: &'a mut EVMContext,
+}
+
+impl PrecompileContext<'_> {
+    pub fn input(&self) -> &[u8] {
+        &self.evm.message.data
+    }
+
+    pub fn caller(&self) -> Address {
+        self.evm.message.caller
+    }
+
+    pub fn value(&self) -> U256 {
+        self.evm.message.value
+    }
+
+    pub fn address(&self) -> Address {
+        self.evm.contract_address
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.evm.is_static
+    }
+
+    pub fn config(&self) -> &EVMConfig {
+        &self.evm.config
+    }
+
+    pub fn gas_available(&self) -> u64 {
+        self.evm.gas_recorder.gas_available() as u64
+    }
+
+    /// Fails and uses all gas if the call doesn't have enough gas left
+    pub fn record_gas_usage(&mut self, gas: u64) -> Result<(), ExecutionError> {
+        self.evm.gas_recorder.record_gas_usage(gas);
+        if !self.evm.gas_recorder.is_valid() {
+            self.evm.gas_recorder.set_gas_usage_to_max();
+            return Err(ExecutionError::InsufficientGas);
+        }
+        Ok(())
+    }
+
+    pub fn set_gas_usage_to_max(&mut self) {
+        self.evm.gas_recorder.set_gas_usage_to_max();
+    }
+}
+
+/// Precompiles added by the host, these replace any built in precompile at the same address
+#[derive(Clone, Default)]
+pub struct PrecompileRegistry {
+    // None removes the built in precompile at the address
+    precompiles: BTreeMap<Address, Option<Arc<dyn Precompile>>>,
+}
+
+impl PrecompileRegistry {
+    pub fn new() -> PrecompileRegistry {
+        PrecompileRegistry::default()
+    }
+
+    pub fn insert(&mut self, address: Address, precompile: impl Precompile + 'static) {
+        self.precompiles.insert(address, Some(Arc::new(precompile)));
+    }
+
+    /// Removes the precompile at the address, including a built in one
+    pub fn remove(&mut self, address: Address) {
+        self.precompiles.insert(address, None);
+    }
+
+    /// Some if the host added or removed a precompile at the address
+    pub(crate) fn get(&self, address: &Address) -> Option<&Option<Arc<dyn Precompile>>> {
+        self.precompiles.get(address)
+    }
+
+    pub(crate) fn addresses(&self) -> impl Iterator<Item = Address> + '_ {
+        self.precompiles.keys().copied()
+    }
+}
+
+impl fmt::Debug for PrecompileRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.precompiles.iter().map(|(address, precompile)| (address, precompile.is_some()))
+            )
+            .finish()
+    }
+}
+
+/// Precompiles can't be compared by value, so registries are equal when they map the same
+/// addresses to the same `Arc` instances, which makes clones of a registry equal
+impl PartialEq for PrecompileRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.precompiles.len() == other.precompiles.len() &&
+            self.precompiles
+                .iter()
+                .zip(other.precompiles.iter())
+                .all(|((a, a_precompile), (b, b_precompile))| {
+                    a == b &&
+                        (match (a_precompile, b_precompile) {
+                            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                            (None, None) => true,
+                            _ => false,
+                        })
+                })
+    }
+}
+
+impl Eq for PrecompileRegistry {}
+
+/// Runs the precompile at the contract address, None if there isn't one so the code is executed
+pub(super) fn execute_precompile<R: Runtime>(
+    evm: &mut EVMContext,
+    runtime: &mut R
+) -> Result<Option<ExecutionResult>, R::Error> {
+    if !is_precompile(&evm.contract_address, &evm.config) {
+        return Ok(None);
+    }
+    if let Some(Some(precompile)) = evm.config.precompiles.get(&evm.contract_address) {
+        let precompile = precompile.clone();
+        let mut precompile_runtime = PrecompileRuntime {
+            runtime: runtime,
+            error: RefCell::new(None),
+        };
+        let result = precompile.execute(&mut PrecompileContext { evm: evm }, &mut precompile_runtime);
+        // The runtime's error is returned even if the precompile ignored it
+        return match (result, precompile_runtime.error.into_inner()) {
+            (_, Some(error)) => Err(error),
+            (Ok(result), None) => Ok(Some(result)),
+            (Err(_), None) => unreachable!("RuntimeError is only created when the runtime fails"),
+        };
+    }
+    let contract: fn(&mut EVMContext) -> ExecutionResult = match evm.contract_address {
+        x if x == *precompile_addresses::ECRECOVER_PRECOMPILE => ecrecover::ecrecover_contract,
+        x if x == *precompile_addresses::SHA256_PRECOMPILE => sha2_256::sha2_256_contract,
+        x if x == *precompile_addresses::RIPEMD160_PRECOMPILE => ripemd_160::ripemd_160_contract,
+        x if x == *precompile_addresses::IDENTITY_PRECOMPILE => identity::identity_contract,
+        x if x == *precompile_addresses::MODEXP_PRECOMPILE => modexp::modexp_contract,
+        x if x == *precompile_addresses::ALTBN128_ADD_PRECOMPILE => ecadd::ecadd_contract,
+        x if x == *precompile_addresses::ALTBN128_MUL_PRECOMPILE => ecmul::ecmul_contract,
+        x if x == *precompile_addresses::ALTBN128_PAIRING_PRECOMPILE => {
+            ecpairing::ecpairing_contract
+        }
+        x if x == *precompile_addresses::BLS12_G1ADD_PRECOMPILE => bls12_381::g1_add_contract,
+        x if x == *precompile_addresses::BLS12_G1MSM_PRECOMPILE => bls12_381::g1_msm_contract,
+        x if x == *precompile_addresses::BLS12_G2ADD_PRECOMPILE => bls12_381::g2_add_contract,
+        x if x == *precompile_addresses::BLS12_G2MSM_PRECOMPILE => bls12_381::g2_msm_contract,
+        x if x == *precompile_addresses::BLS12_PAIRING_PRECOMPILE => bls12_381::pairing_contract,
+        x if x == *precompile_addresses::BLS12_MAP_FP_TO_G1_PRECOMPILE => {
+            bls12_381::map_fp_to_g1_contract
+        }
+        x if x == *precompile_addresses::BLS12_MAP_FP2_TO_G2_PRECOMPILE => {
+            bls12_381::map_fp2_to_g2_contract
+        }
+        x if x == *precompile_addresses::P256VERIFY_PRECOMPILE => p256_verify::p256_verify_contract,
+        _ => {
+            return Ok(None);
+        }
+    };
+    Ok(Some(contract(evm)))
+}
+
+// Gives precompiles a runtime with a known error type, keeping the first error of the host runtime
+struct PrecompileRuntime<'a, R: Runtime> {
+    runtime: &'a mut R,
+    error: RefCell<Option<R::Error>>,
+}
+
+impl<R: Runtime> PrecompileRuntime<'_, R> {
+    fn check<T>(&self, result: Result<T, R::Error>) -> Result<T, RuntimeError> {
+        result.map_err(|error| {
+            self.error.borrow_mut().get_or_insert(error);
+            RuntimeError(())
+        })
+    }
+}
+
+macro_rules! forward {
+    ($(fn $name:ident(&self $(, $arg:ident: $arg_type:ty)*) -> $return_type:ty;)*) => {
+        $(
+            fn $name(&self $(, $arg: $arg_type)*) -> Result<$return_type, RuntimeError> {
+                self.check(self.runtime.$name($($arg),*))
+            }
+        )*
+    };
+    ($(fn $name:ident(&mut self $(, $arg:ident: $arg_type:ty)*);)*) => {
+        $(
+            fn $name(&mut self $(, $arg: $arg_type)*) -> Result<(), RuntimeError> {
+                let result = self.runtime.$name($($arg),*);
+                self.check(result)
+            }
+        )*
+    };
+}
+
+impl<R: Runtime> Runtime for PrecompileRuntime<'_, R> {
+    type Error = RuntimeError;
+
+    forward! {
+        fn block_hash(&self, block_number: U256) -> H256;
+        fn block_number(&self) -> U256;
+        fn block_coinbase(&self) -> Address;
+        fn block_timestamp(&self) -> U256;
+        fn block_difficulty(&self) -> U256;
+        fn block_randomness(&self) -> U256;
+        fn block_gas_limit(&self) -> U256;
+        fn block_base_fee_per_gas(&self) -> U256;
+        fn block_excess_blob_gas(&self) -> U256;
+        fn chain_id(&self) -> U256;
+        fn balance(&self, address: Address) -> U256;
+        fn code_size(&self, address: Address) -> U256;
+        fn code_hash(&self, address: Address) -> H256;
+        fn code(&self, address: Address) -> Vec<u8>;
+        fn exists(&self, address: Address) -> bool;
+        fn nonce(&self, address: Address) -> U256;
+        fn read_storage(&self, address: Address, index: StorageKey) -> StorageValue;
+        fn read_original_storage(&self, address: Address, index: StorageKey) -> StorageValue;
+        fn read_transient_storage(&self, address: Address, index: StorageKey) -> StorageValue;
+        fn is_deleted(&self, address: Address) -> bool;
+        fn is_cold(&self, address: Address) -> bool;
+        fn is_cold_index(&self, address: Address, index: StorageKey) -> bool;
+        fn is_hot(&self, address: Address) -> bool;
+        fn is_hot_index(&self, address: Address, index: StorageKey) -> bool;
+    }
+
+    forward! {
+        fn mark_hot(&mut self, address: Address);
+        fn mark_hot_index(&mut self, address: Address, index: StorageKey);
+        fn set_storage(&mut self, address: Address, index: StorageKey, value: StorageValue);
+        fn set_transient_storage(&mut self, address: Address, index: StorageKey, value: StorageValue);
+        fn clear_transient_storage(&mut self);
+        fn mark_delete(&mut self, address: Address);
+        fn reset_storage(&mut self, address: Address);
+        fn set_code(&mut self, address: Address, code: Vec<u8>);
+        fn reset_balance(&mut self, address: Address);
+        fn deposit(&mut self, target: Address, value: U256);
+        fn withdrawal(&mut self, source: Address, value: U256);
+        fn increase_nonce(&mut self, address: Address);
+        fn create_contract(&mut self, address: Address, code: Vec<u8>);
+        fn set_contract_code(&mut self, address: Address, code: Vec<u8>);
+        fn add_context(&mut self);
+        fn merge_context(&mut self);
+        fn revert_context(&mut self);
+    }
+}
@@ -0,0 +1,76 @@
+use num_bigint::BigUint;
+use primitive_types::U256;
+
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::EVMContext,
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+/// Computes base ** exponent % modulus for arbitrary length numbers (EIP-198)
+pub fn modexp_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let input = evm.message.data.clone();
+    let base_length = U256::from_big_endian(&read_padded(&input, U256::zero(), 32));
+    let exponent_length = U256::from_big_endian(&read_padded(&input, U256::from(32), 32));
+    let modulus_length = U256::from_big_endian(&read_padded(&input, U256::from(64), 32));
+    let exponent_offset = base_length.saturating_add(U256::from(96));
+    let exponent_head = U256::from_big_endian(
+        &read_padded(&input, exponent_offset, exponent_length.min(U256::from(32)).as_usize())
+    );
+    let cost = (DynamicPreCompileCosts::ModExp {
+        base_length: base_length,
+        exponent_length: exponent_length,
+        modulus_length: modulus_length,
+        exponent_head: exponent_head,
+    }).cost(evm.config.hardfork);
+    // Checked before recording as the cost can be too large to add to the gas usage
+    if cost > (evm.gas_recorder.gas_available() as u64) {
+        evm.gas_recorder.set_gas_usage_to_max();
+        return ExecutionResult::Error(ExecutionError::InsufficientGas);
+    }
+    evm.gas_recorder.record_gas_usage(cost);
+    if modulus_length.is_zero() {
+        return ExecutionResult::Success(ExecutionSuccess::Return(vec![]));
+    }
+    // Any length that was paid for fits in a usize
+    let (base_length, exponent_length, modulus_length) = (
+        base_length.as_usize(),
+        exponent_length.as_usize(),
+        modulus_length.as_usize(),
+    );
+    let base = BigUint::from_bytes_be(&read_padded(&input, U256::from(96), base_length));
+    let exponent = BigUint::from_bytes_be(&read_padded(&input, exponent_offset, exponent_length));
+    let modulus = BigUint::from_bytes_be(
+        &read_padded(&input, exponent_offset + exponent_length, modulus_length)
+    );
+    let mut output = vec![0u8; modulus_length];
+    if modulus != BigUint::ZERO {
+        let result = base.modpow(&exponent, &modulus).to_bytes_be();
+        output[modulus_length - result.len()..].copy_from_slice(&result);
+    }
+    ExecutionResult::Success(ExecutionSuccess::Return(output))
+}
+
+/// Reads `length` bytes from `offset`, input past the end is treated as zeros
+fn read_padded(input: &[u8], offset: U256, length: usize) -> Vec<u8> {
+    let mut output = vec![0u8; length];
+    if offset < U256::from(input.len()) {
+        let offset = offset.as_usize();
+        let end = input.len().min(offset.saturating_add(length));
+        output[..end - offset].copy_from_slice(&input[offset..end]);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_padded() {
+        let input = [1u8, 2, 3];
+        assert_eq!(read_padded(&input, U256::one(), 4), vec![2, 3, 0, 0]);
+        assert_eq!(read_padded(&input, U256::from(3), 2), vec![0, 0]);
+        assert_eq!(read_padded(&input, U256::MAX, 2), vec![0, 0]);
+    }
+}
@@ -0,0 +1,53 @@
+use p256::ecdsa::{ signature::hazmat::PrehashVerifier, Signature, VerifyingKey };
+use p256::EncodedPoint;
+
+use crate::{
+    configs::gas_costs::precompile_costs::G_P256VERIFY,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+/// Input is the message hash, r, s and the x and y coordinates of the public key
+const INPUT_LENGTH: usize = 160;
+
+/// Verifies a secp256r1 signature (RIP-7212), returns 1 if it is valid and no bytes otherwise
+pub fn p256_verify_contract(evm: &mut EVMContext) -> ExecutionResult {
+    evm.gas_recorder.record_gas_usage(G_P256VERIFY);
+    return_if_gas_too_high!(evm.gas_recorder);
+    let input = &evm.message.data;
+    if input.len() != INPUT_LENGTH || !p256_verify(input) {
+        return ExecutionResult::Success(ExecutionSuccess::Return(vec![]));
+    }
+    let mut result = vec![0u8; 32];
+    result[31] = 1;
+    ExecutionResult::Success(ExecutionSuccess::Return(result))
+}
+
+fn p256_verify(input: &[u8]) -> bool {
+    let (hash, r, s, x, y) = (
+        &input[0..32],
+        &input[32..64],
+        &input[64..96],
+        &input[96..128],
+        &input[128..160],
+    );
+    // Rejects r or s that are zero or not below the curve order
+    let signature = match Signature::from_scalars(
+        *p256::FieldBytes::from_slice(r),
+        *p256::FieldBytes::from_slice(s)
+    ) {
+        Ok(signature) => signature,
+        Err(_) => {
+            return false;
+        }
+    };
+    // Rejects points that aren't on the curve, including the point at infinity
+    let point = EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+    let key = match VerifyingKey::from_encoded_point(&point) {
+        Ok(key) => key,
+        Err(_) => {
+            return false;
+        }
+    };
+    key.verify_prehash(hash, &signature).is_ok()
+}
@@ -0,0 +1,42 @@
+use ripemd::{ Digest, Ripemd160 };
+
+use crate::{
+    configs::gas_costs::DynamicPreCompileCosts,
+    evm_logic::evm::{ macros::return_if_gas_too_high, EVMContext },
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+};
+
+pub fn ripemd_160_contract(evm: &mut EVMContext) -> ExecutionResult {
+    let input = &evm.message.data;
+    let cost = (DynamicPreCompileCosts::Ripemd160 {
+        data_word_size: input.len().div_ceil(32),
+    }).cost(evm.config.hardfork);
+    evm.gas_recorder.record_gas_usage(cost);
+    return_if_gas_too_high!(evm.gas_recorder);
+    ExecutionResult::Success(ExecutionSuccess::Return(ripemd_160(&evm.message.data).to_vec()))
+}
+
+/// The 20 byte digest left padded to a 32 byte word
+fn ripemd_160(input: &[u8]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    result[12..].copy_from_slice(&Ripemd160::digest(input));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ripemd_160() {
+        assert_eq!(
+            ripemd_160(b""),
+            hex!("0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31")
+        );
+        assert_eq!(
+            ripemd_160(b"abc"),
+            hex!("0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc")
+        );
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
 pub fn sha2_256_contract(evm: &mut EVMContext) -> ExecutionResult {
     // Try to apply gas cost, if not enough return no bytes
     let input = evm.message.data.clone();
-    let cost = (DynamicPreCompileCosts::Sha256 { data_word_size: (input.len() + 31) / 32 }).cost();
+    let cost = (DynamicPreCompileCosts::Sha256 { data_word_size: (input.len() + 31) / 32 }).cost(evm.config.hardfork);
     if evm.gas_recorder.gas_available() < cost.try_into().unwrap() {
         evm.gas_recorder.record_gas_usage(evm.gas_recorder.gas_available().try_into().unwrap());
         return ExecutionResult::Success(ExecutionSuccess::Return(vec![]));
@@ -0,0 +1,141 @@
+use primitive_types::{ H256, U256 };
+
+use crate::evm_logic::util::keccak256;
+use crate::result::ExecutionResult;
+
+use super::Log;
+
+const BLOOM_SIZE: usize = 256;
+
+/// The outcome of a transaction as stored in a block. Receipts hold a status rather than the
+/// intermediate state root, as they do from Byzantium (EIP-658)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// Type of the transaction (EIP-2718), 0 for legacy transactions
+    pub transaction_type: u8,
+    pub success: bool,
+    /// Gas used by this transaction and the ones before it in the block
+    pub cumulative_gas_used: u64,
+    pub logs_bloom: [u8; BLOOM_SIZE],
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    pub fn new(
+        transaction_type: u8,
+        result: &ExecutionResult,
+        cumulative_gas_used: u64,
+        logs: Vec<Log>
+    ) -> Receipt {
+        Receipt {
+            transaction_type: transaction_type,
+            success: matches!(result, ExecutionResult::Success(_)),
+            cumulative_gas_used: cumulative_gas_used,
+            logs_bloom: logs_bloom(&logs),
+            logs: logs,
+        }
+    }
+
+    /// RLP of the receipt, prefixed with the transaction type unless it is a legacy transaction
+    pub fn encode(&self) -> Vec<u8> {
+        let data = ethereum::EIP658ReceiptData {
+            status_code: self.success as u8,
+            used_gas: U256::from(self.cumulative_gas_used),
+            logs_bloom: self.logs_bloom.into(),
+            logs: self.logs.iter().map(to_ethereum_log).collect(),
+        };
+        let mut encoded = vec![];
+        if self.transaction_type != 0 {
+            encoded.push(self.transaction_type);
+        }
+        encoded.extend_from_slice(&rlp::encode(&data));
+        encoded
+    }
+}
+
+/// Sets 3 bits for the address and each topic of every log, chosen by the keccak hash
+pub fn logs_bloom(logs: &[Log]) -> [u8; BLOOM_SIZE] {
+    let mut bloom = [0u8; BLOOM_SIZE];
+    for log in logs {
+        accrue_bloom(&mut bloom, log.address.as_bytes());
+        for topic in &log.topics {
+            accrue_bloom(&mut bloom, topic.as_bytes());
+        }
+    }
+    bloom
+}
+
+fn accrue_bloom(bloom: &mut [u8; BLOOM_SIZE], input: &[u8]) {
+    let hash = keccak256(input);
+    for pair in hash.as_bytes()[..6].chunks(2) {
+        let bit = (((pair[0] as usize) << 8) | (pair[1] as usize)) % (BLOOM_SIZE * 8);
+        bloom[BLOOM_SIZE - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
+/// Keccak hash of the RLP list of logs, which the official tests check
+pub fn logs_hash(logs: &[Log]) -> H256 {
+    let logs: Vec<ethereum::Log> = logs.iter().map(to_ethereum_log).collect();
+    keccak256(&rlp::encode_list(&logs))
+}
+
+/// Root of the trie of a block's receipts, keyed by the RLP of their index
+pub fn receipts_root(receipts: &[Receipt]) -> H256 {
+    ethereum::util::ordered_trie_root(receipts.iter().map(Receipt::encode))
+}
+
+fn to_ethereum_log(log: &Log) -> ethereum::Log {
+    ethereum::Log {
+        address: log.address,
+        topics: log.topics.clone(),
+        data: log.data.clone().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Address;
+
+    #[test]
+    fn test_empty_receipts() {
+        assert_eq!(
+            logs_hash(&[]),
+            H256::from_slice(
+                &hex::decode("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347").unwrap()
+            )
+        );
+        assert_eq!(
+            receipts_root(&[]),
+            H256::from_slice(
+                &hex::decode("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421").unwrap()
+            )
+        );
+        assert_eq!(logs_bloom(&[]), [0u8; BLOOM_SIZE]);
+    }
+
+    #[test]
+    fn test_logs_bloom() {
+        let log = Log {
+            address: Address::from_slice(
+                &hex::decode("ef2d6d194084c2de36e0dabfce45d046b37d1106").unwrap()
+            ),
+            topics: vec![
+                H256::from_slice(
+                    &hex::decode(
+                        "02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc"
+                    ).unwrap()
+                )
+            ],
+            data: vec![],
+        };
+        let mut expected = [0u8; BLOOM_SIZE];
+        expected[20] = 0x10;
+        expected[99] = 0x02;
+        expected[100] = 0x02;
+        expected[123] = 0x08;
+        expected[128] = 0x10;
+        expected[155] = 0x10;
+        assert_eq!(logs_bloom(&[log]), expected);
+    }
+}
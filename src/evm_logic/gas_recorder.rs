@@ -1,24 +1,32 @@
 use core::panic;
 
+use crate::configs::gas_costs::{ self, static_costs };
+use crate::configs::hardfork::Hardfork;
 use crate::result::ExecutionResult;
 
 #[derive(Copy, Clone)]
 pub struct GasRecorder {
     pub gas_input: usize,
     pub gas_usage: usize,
-    pub gas_refunds: usize,
+    // Can be negative within a call when an earlier refund is undone (EIP-2200)
+    pub gas_refunds: i64,
 }
 
 impl GasRecorder {
-    pub fn usage_with_refunds(&self) -> usize {
+    pub fn usage_with_refunds(&self, hardfork: Hardfork) -> usize {
         // println!("Gas usage: {:x}", self.gas_usage);
         // println!("Gas refunds: {:x}", self.gas_refunds);
-
-        self.gas_usage - self.gas_refunds.min(self.gas_usage / 2)
+        let max_refund_quotient = if hardfork.is_enabled(Hardfork::London) {
+            static_costs::MAX_REFUND_QUOTIENT_LONDON
+        } else {
+            static_costs::MAX_REFUND_QUOTIENT
+        };
+        let refunds = self.gas_refunds.max(0) as usize;
+        self.gas_usage - refunds.min(self.gas_usage / (max_refund_quotient as usize))
     }
 
     pub fn gas_available(&self) -> usize {
-        self.gas_input - self.usage_with_refunds()
+        self.gas_input.saturating_sub(self.gas_usage)
     }
 
     pub fn is_valid(&self) -> bool {
@@ -28,17 +36,12 @@ impl GasRecorder {
     pub fn set_gas_usage_to_max(&mut self) {
         self.gas_usage = self.gas_input;
     }
-    // TODO unit test
-    pub fn is_valid_with_refunds(&self) -> bool {
-        self.gas_usage - self.gas_refunds.min(self.gas_usage / 2) <= self.gas_input
-    }
-
     pub fn record_gas_usage(&mut self, gas: u64) {
         self.gas_usage += gas as usize;
     }
 
-    pub fn record_refund(&mut self, gas: u64) {
-        self.gas_refunds += gas as usize;
+    pub fn record_refund(&mut self, gas: i64) {
+        self.gas_refunds += gas;
     }
 
     // TODO unit test
@@ -59,8 +62,8 @@ impl GasRecorder {
         self.gas_usage += memory_expansion_cost;
     }
 
-    pub fn record_call_data_gas_usage(&mut self, data: &[u8]) {
-        let cost = call_data_gas_cost(data);
+    pub fn record_call_data_gas_usage(&mut self, data: &[u8], hardfork: Hardfork) {
+        let cost = call_data_gas_cost(data, hardfork);
         self.record_gas_usage(cost);
     }
 
@@ -84,15 +87,14 @@ impl GasRecorder {
 }
 
 #[inline]
-fn call_data_gas_cost(data: &[u8]) -> u64 {
+fn call_data_gas_cost(data: &[u8], hardfork: Hardfork) -> u64 {
+    let non_zero_cost = gas_costs::tx_data_non_zero_cost(hardfork);
     let mut cost = 0;
     for byte in data {
         if *byte == 0 {
-            // TODO put into static costs
-            cost += 4;
+            cost += static_costs::G_TX_DATA_ZERO;
         } else {
-            // TODO put into static costs
-            cost += 16;
+            cost += non_zero_cost;
         }
     }
     cost
@@ -1,3 +1,4 @@
+pub mod eof;
 pub mod evm;
 pub mod gas_recorder;
 pub mod util;
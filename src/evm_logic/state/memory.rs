@@ -96,6 +96,34 @@ impl Memory {
         ExecutionResult::InProgress
     }
 
+    /// Copy a range of memory to another location in the same memory, the ranges may overlap
+    #[inline]
+    pub fn copy_within(
+        &mut self,
+        read_address: usize,
+        write_address: usize,
+        length: usize,
+        gas_recorder: &mut GasRecorder
+    ) -> ExecutionResult {
+        if length == 0 {
+            return ExecutionResult::InProgress;
+        }
+        if
+            write_address.checked_add(length).is_none() ||
+            read_address.checked_add(length).is_none()
+        {
+            gas_recorder.record_gas_usage(gas_recorder.gas_input as u64);
+            return ExecutionResult::Error(ExecutionError::InsufficientGas);
+        }
+        // Expansion is charged for whichever range ends furthest into memory
+        let end = read_address.max(write_address) + length;
+        if end > self.bytes.len() {
+            return_if_error!(self.expand(end, Some(gas_recorder)));
+        }
+        self.bytes.copy_within(read_address..read_address + length, write_address);
+        ExecutionResult::InProgress
+    }
+
     // TODO refactor this to be cleaner, (currently just here because of logs)
     #[inline]
     pub fn copy_from_no_local_cost(
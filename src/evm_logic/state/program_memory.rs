@@ -2,6 +2,7 @@ use std::ops::Index;
 
 use primitive_types::U256;
 
+use crate::evm_logic::eof::{ self, EofContainer };
 use crate::evm_logic::evm::macros::return_if_error;
 use crate::evm_logic::{
     evm::macros::{return_if_gas_too_high, return_tuple_if_error},
@@ -13,6 +14,8 @@ use crate::result::{ExecutionError, ExecutionResult};
 #[derive(Default)]
 pub struct ProgramMemory {
     pub bytes: Vec<u8>,
+    // Section layout when the code is an EOF container
+    pub eof: Option<EofContainer>,
 }
 
 impl ProgramMemory {
@@ -26,10 +29,44 @@ impl ProgramMemory {
     pub fn from(bytes: &[u8]) -> ProgramMemory {
         let memory = ProgramMemory {
             bytes: bytes.to_vec(),
+            eof: None,
         };
         memory
     }
 
+    /// Loads code as an EOF container if it is a valid one, otherwise as legacy code
+    #[inline]
+    pub fn from_eof(bytes: &[u8]) -> ProgramMemory {
+        ProgramMemory {
+            bytes: bytes.to_vec(),
+            eof: eof::is_eof(bytes).then(|| eof::validate(bytes).ok()).flatten(),
+        }
+    }
+
+    /// True for code with the EOF magic that isn't a valid container, which can't be executed
+    #[inline]
+    pub fn is_invalid_eof(&self) -> bool {
+        self.eof.is_none() && eof::is_eof(&self.bytes)
+    }
+
+    /// Data section of an EOF container, empty for legacy code
+    #[inline]
+    pub fn eof_data(&self) -> &[u8] {
+        match &self.eof {
+            Some(container) => &self.bytes[container.data.clone()],
+            None => &[],
+        }
+    }
+
+    /// Offset of the first instruction to execute
+    #[inline]
+    pub fn entry_point(&self) -> usize {
+        match &self.eof {
+            Some(container) => container.code_sections[0].start,
+            None => 0,
+        }
+    }
+
 }
 
 impl Index<usize> for ProgramMemory {
@@ -2,7 +2,7 @@ use std::{ ops::Not, str::FromStr };
 
 use lazy_static::lazy_static;
 use num256::{ Int256, Uint256 };
-use primitive_types::{ H256, U256, U512 };
+use primitive_types::{ H160, H256, U256, U512 };
 use sha3::{ Digest, Keccak256 };
 
 pub const ZERO: U256 = U256::zero();
@@ -52,6 +52,15 @@ pub fn u256_to_h256(v: U256) -> H256 {
     r
 }
 
+/// Keeps the low 20 bytes, the way addresses are read from the stack
+pub fn u256_to_address(v: U256) -> H160 {
+    H160::from(u256_to_h256(v))
+}
+
+pub fn address_to_u256(v: H160) -> U256 {
+    h256_to_u256(H256::from(v))
+}
+
 pub fn u256_to_array(v: U256) -> [u8; 32] {
     let mut x: [u8; 32] = [0; 32];
     v.to_big_endian(&mut x);
@@ -2,5 +2,17 @@ mod configs;
 mod evm_logic;
 pub mod runtime;
 pub mod result;
-pub use evm_logic::evm::execute_transaction;
+pub use configs::evm_config::EVMConfig;
+pub use configs::hardfork::Hardfork;
+pub use evm_logic::evm::{
+    execute_transaction,
+    logs_bloom,
+    logs_hash,
+    receipts_root,
+    AccessListItem,
+    Authorization,
+    Log,
+    Receipt,
+};
+pub use evm_logic::evm::precompiles::{ Precompile, PrecompileContext, PrecompileRegistry, RuntimeError };
 pub use evm_logic::util;
@@ -5,6 +5,7 @@ pub enum ExecutionError {
     InsufficientBalance,
     InvalidAddress,
     InvalidJump,
+    InvalidOpcode(u8),
     ExcitedEarly,
     InsufficientValuesOnStack,
     StackOverflow,
@@ -12,11 +13,31 @@ pub enum ExecutionError {
     ModifyStaticState,
     InvalidMemSize,
     InvalidMemoryAccess,
+    InitCodeSizeExceeded,
+    CodeSizeExceeded,
+    InvalidCodePrefix,
+    InvalidEofContainer,
+    InvalidGasPrice,
+    InvalidPrecompileInput,
     Halted,
     StackUnderflow,
     Revert(Vec<u8>),
 }
 
+/// Errors that abort the whole transaction, unlike an ExecutionError which only fails the call it
+/// occurs in, the runtime's state should be discarded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FatalError<E> {
+    // The runtime failed to read or write the state
+    Database(E),
+}
+
+impl<E> From<E> for FatalError<E> {
+    fn from(error: E) -> Self {
+        FatalError::Database(error)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ExecutionSuccess {
     // This should never occur
@@ -54,3 +75,10 @@ impl  ExecutionResult {
         }
     }
 }
+
+// Lets the macros that return early be used in functions that can also fail on a runtime error
+impl<E> From<ExecutionResult> for Result<ExecutionResult, E> {
+    fn from(result: ExecutionResult) -> Self {
+        Ok(result)
+    }
+}
@@ -1,65 +1,103 @@
-use primitive_types::{H256, U256};
+use primitive_types::{H160, H256, U256};
+
+/// Address of an account
+pub type Address = H160;
+/// Index of a storage slot
+pub type StorageKey = H256;
+/// Value of a storage slot
+pub type StorageValue = H256;
+
 pub trait Runtime {
     /*
     Runtime that can be used by the EVM, requires the following methods to be implemented.
     The runtime provides the means with which the EVM can interact with the global state
+    Methods return an error if the state can't be accessed, this aborts the transaction
     */
 
+    type Error;
+
     // Block information
-    fn block_hash(&self, block_number: U256) -> H256;
-    fn block_number(&self) -> U256;
-    fn block_coinbase(&self) -> U256;
-    fn block_timestamp(&self) -> U256;
-    fn block_difficulty(&self) -> U256;
-    fn block_randomness(&self) -> U256;
-    fn block_gas_limit(&self) -> U256;
-    fn block_base_fee_per_gas(&self) -> U256;
-    fn chain_id(&self) -> U256;
+    fn block_hash(&self, block_number: U256) -> Result<H256, Self::Error>;
+    fn block_number(&self) -> Result<U256, Self::Error>;
+    fn block_coinbase(&self) -> Result<Address, Self::Error>;
+    fn block_timestamp(&self) -> Result<U256, Self::Error>;
+    fn block_difficulty(&self) -> Result<U256, Self::Error>;
+    fn block_randomness(&self) -> Result<U256, Self::Error>;
+    fn block_gas_limit(&self) -> Result<U256, Self::Error>;
+    fn block_base_fee_per_gas(&self) -> Result<U256, Self::Error>;
+    fn block_excess_blob_gas(&self) -> Result<U256, Self::Error>;
+    fn chain_id(&self) -> Result<U256, Self::Error>;
 
     // Contract information
-    fn balance(&self, address: U256) -> U256;
-    fn code_size(&self, address: U256) -> U256;
-    fn code_hash(&self, address: U256) -> H256;
-    fn code(&self, address: U256) -> Vec<u8>;
-    fn exists(&self, address: U256) -> bool;
-    fn nonce(&self, address: U256) -> U256;
-    fn read_storage(&self, address: U256, index: U256) -> H256;
-    fn read_original_storage(&self, address: U256, index: U256) -> H256;
+    fn balance(&self, address: Address) -> Result<U256, Self::Error>;
+    fn code_size(&self, address: Address) -> Result<U256, Self::Error>;
+    fn code_hash(&self, address: Address) -> Result<H256, Self::Error>;
+    fn code(&self, address: Address) -> Result<Vec<u8>, Self::Error>;
+    fn exists(&self, address: Address) -> Result<bool, Self::Error>;
+    fn nonce(&self, address: Address) -> Result<U256, Self::Error>;
+    fn read_storage(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error>;
+    fn read_original_storage(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error>;
+    // Transient storage (EIP-1153), must be reverted with the context and cleared after each transaction
+    fn read_transient_storage(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error>;
 
     // Modify Contract State
-    fn is_deleted(&self, address: U256) -> bool;
-    fn is_cold(&self, address: U256) -> bool;
-    fn is_cold_index(&self, address: U256, index: U256) -> bool;
-    fn is_hot(&self, address: U256) -> bool {
-        !self.is_cold(address)
+    fn is_deleted(&self, address: Address) -> Result<bool, Self::Error>;
+    fn is_cold(&self, address: Address) -> Result<bool, Self::Error>;
+    fn is_cold_index(&self, address: Address, index: StorageKey) -> Result<bool, Self::Error>;
+    fn is_hot(&self, address: Address) -> Result<bool, Self::Error> {
+        Ok(!self.is_cold(address)?)
     }
-    fn is_hot_index(&self, address: U256, index: U256) -> bool {
-        !self.is_cold_index(address, index)
+    fn is_hot_index(&self, address: Address, index: StorageKey) -> Result<bool, Self::Error> {
+        Ok(!self.is_cold_index(address, index)?)
     }
-    fn mark_hot(&mut self, address: U256);
-    fn mark_hot_index(&mut self, address: U256, index: U256);
-    fn set_storage(&mut self, address: U256, index: U256, value: H256);
-    fn mark_delete(&mut self, address: U256);
-    fn reset_storage(&mut self, address: U256);
-    fn set_code(&mut self, address: U256, code: Vec<u8>);
-    fn reset_balance(&mut self, address: U256);
-    fn deposit(&mut self, target: U256, value: U256);
-    fn withdrawal(&mut self, source: U256, value: U256);
-    fn increase_nonce(&mut self, address: U256);
+    fn mark_hot(&mut self, address: Address) -> Result<(), Self::Error>;
+    fn mark_hot_index(&mut self, address: Address, index: StorageKey) -> Result<(), Self::Error>;
+    fn set_storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+        value: StorageValue,
+    ) -> Result<(), Self::Error>;
+    fn set_transient_storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+        value: StorageValue,
+    ) -> Result<(), Self::Error>;
+    fn clear_transient_storage(&mut self) -> Result<(), Self::Error>;
+    fn mark_delete(&mut self, address: Address) -> Result<(), Self::Error>;
+    fn reset_storage(&mut self, address: Address) -> Result<(), Self::Error>;
+    fn set_code(&mut self, address: Address, code: Vec<u8>) -> Result<(), Self::Error>;
+    fn reset_balance(&mut self, address: Address) -> Result<(), Self::Error>;
+    fn deposit(&mut self, target: Address, value: U256) -> Result<(), Self::Error>;
+    fn withdrawal(&mut self, source: Address, value: U256) -> Result<(), Self::Error>;
+    fn increase_nonce(&mut self, address: Address) -> Result<(), Self::Error>;
 
     // Create Contract
     fn create_contract(
         &mut self,
-        address: U256,
+        address: Address,
         code: Vec<u8>,
-    );
+    ) -> Result<(), Self::Error>;
     fn set_contract_code(
         &mut self,
-        address: U256,
+        address: Address,
         code: Vec<u8>,
-    );
+    ) -> Result<(), Self::Error>;
 
-    fn add_context(&mut self);
-    fn merge_context(&mut self);
-    fn revert_context(&mut self);
+    fn add_context(&mut self) -> Result<(), Self::Error>;
+    fn merge_context(&mut self) -> Result<(), Self::Error>;
+    fn revert_context(&mut self) -> Result<(), Self::Error>;
 }
@@ -0,0 +1,2 @@
+mod execution_tests;
+mod mocks;
@@ -0,0 +1,64 @@
+use ethereum_evm::{ execute_transaction, AccessListItem, Hardfork };
+use primitive_types::{ H256, U256 };
+
+use super::util::{ add_contract, address, is_success, runtime, CONTRACT, GAS_LIMIT, SENDER };
+
+/// Gas used by a Berlin transaction calling `code` with `access_list`, 0xdd is an empty account
+fn gas_usage(code: Vec<u8>, access_list: &[AccessListItem]) -> usize {
+    let mut runtime = runtime(code);
+    add_contract(&mut runtime, address(0xdd), vec![]);
+    let (result, gas_usage, _) = execute_transaction(
+        &mut runtime,
+        &Hardfork::Berlin.into(),
+        Some(address(CONTRACT)),
+        address(SENDER),
+        GAS_LIMIT,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        &[],
+        &[],
+        access_list,
+        &[],
+        false
+    ).unwrap();
+    assert!(is_success(&result));
+    gas_usage
+}
+
+fn item(address: u64, storage_keys: &[u64]) -> AccessListItem {
+    AccessListItem {
+        address: super::util::address(address),
+        storage_keys: storage_keys.iter().map(|key| H256::from_low_u64_be(*key)).collect(),
+    }
+}
+
+#[test]
+fn test_access_list_intrinsic_gas() {
+    assert_eq!(gas_usage(vec![0x00], &[]), 21000);
+    // 2400 per address and 1900 per storage key
+    assert_eq!(gas_usage(vec![0x00], &[item(0xdd, &[])]), 21000 + 2400);
+    assert_eq!(gas_usage(vec![0x00], &[item(0xdd, &[1, 2])]), 21000 + 2400 + 2 * 1900);
+    assert_eq!(
+        gas_usage(vec![0x00], &[item(0xdd, &[1]), item(0xee, &[])]),
+        21000 + 2 * 2400 + 1900
+    );
+}
+
+#[test]
+fn test_access_list_storage_keys_are_warm() {
+    // SLOAD(1)
+    let code = vec![0x60, 0x01, 0x54, 0x00];
+    assert_eq!(gas_usage(code.clone(), &[]), 21000 + 3 + 2100);
+    assert_eq!(gas_usage(code.clone(), &[item(CONTRACT, &[1])]), 21000 + 2400 + 1900 + 3 + 100);
+    // Only the listed keys are warm
+    assert_eq!(gas_usage(code, &[item(CONTRACT, &[2])]), 21000 + 2400 + 1900 + 3 + 2100);
+}
+
+#[test]
+fn test_access_list_addresses_are_warm() {
+    // BALANCE(0xdd)
+    let code = vec![0x60, 0xdd, 0x31, 0x00];
+    assert_eq!(gas_usage(code.clone(), &[]), 21000 + 3 + 2600);
+    assert_eq!(gas_usage(code, &[item(0xdd, &[])]), 21000 + 2400 + 3 + 100);
+}
@@ -0,0 +1,66 @@
+use ethereum_evm::{
+    execute_transaction,
+    result::{ ExecutionError, ExecutionResult },
+    Hardfork,
+};
+use primitive_types::{ H256, U256 };
+
+use super::util::{ address, call, is_success, runtime, storage, CONTRACT, GAS_LIMIT, SENDER };
+
+#[test]
+fn test_blobhash() {
+    // SSTORE(0, BLOBHASH(0)) SSTORE(1, BLOBHASH(1)) SSTORE(2, BLOBHASH(2))
+    let code = vec![
+        0x5f, 0x49, 0x5f, 0x55, 0x60, 0x01, 0x49, 0x60, 0x01, 0x55, 0x60, 0x02, 0x49, 0x60, 0x02,
+        0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    let blob_hashes = [H256::repeat_byte(0x01), H256::repeat_byte(0x02)];
+    let (result, _, _) = execute_transaction(
+        &mut runtime,
+        &Hardfork::Cancun.into(),
+        Some(address(CONTRACT)),
+        address(SENDER),
+        GAS_LIMIT,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        &[],
+        &blob_hashes,
+        &[],
+        &[],
+        false
+    ).unwrap();
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), blob_hashes[0]);
+    assert_eq!(storage(&runtime, address(CONTRACT), 1), blob_hashes[1]);
+    // Out of range
+    assert_eq!(storage(&runtime, address(CONTRACT), 2), H256::zero());
+}
+
+#[test]
+fn test_blobbasefee() {
+    // SSTORE(0, BLOBBASEFEE)
+    let code = vec![0x4a, 0x5f, 0x55, 0x00];
+    // fake_exponential(1, excess_blob_gas, 3338477)
+    for (excess_blob_gas, blob_base_fee) in [(0, 1), (3338477, 2), (33384770, 22026)] {
+        let mut cancun = runtime(code.clone());
+        cancun.block_excess_blob_gas = U256::from(excess_blob_gas);
+        let (result, _, _) = call(&mut cancun, &Hardfork::Cancun.into());
+        assert!(is_success(&result));
+        assert_eq!(storage(&cancun, address(CONTRACT), 0), H256::from_low_u64_be(blob_base_fee));
+    }
+}
+
+#[test]
+fn test_blob_opcodes_before_cancun() {
+    for opcode in [0x49, 0x4a] {
+        // PUSH1 0 followed by the opcode
+        let mut shanghai = runtime(vec![0x60, 0x00, opcode, 0x00]);
+        let (result, gas_usage, _) = call(&mut shanghai, &Hardfork::Shanghai.into());
+        assert!(
+            matches!(result, ExecutionResult::Error(ExecutionError::InvalidOpcode(op)) if op == opcode)
+        );
+        assert_eq!(gas_usage, GAS_LIMIT as usize);
+    }
+}
@@ -0,0 +1,304 @@
+use ethereum_evm::{
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+    Hardfork,
+};
+
+use super::util::run_precompile;
+
+const G1ADD: u64 = 0x0b;
+const G1MSM: u64 = 0x0c;
+const G2ADD: u64 = 0x0d;
+const G2MSM: u64 = 0x0e;
+const PAIRING: u64 = 0x0f;
+const MAP_FP_TO_G1: u64 = 0x10;
+const MAP_FP2_TO_G2: u64 = 0x11;
+
+/// Base field modulus
+const P: &str =
+    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab";
+/// Group order
+const R: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+
+// Multiples of the generators, as affine coordinates
+const G1: [&str; 2] = [
+    "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+];
+const G1_NEGATED: [&str; 2] = [
+    "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    "114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca",
+];
+const G1_DOUBLE: [&str; 2] = [
+    "0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e",
+    "166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28",
+];
+const G1_TRIPLE: [&str; 2] = [
+    "09ece308f9d1f0131765212deca99697b112d61f9be9a5f1f3780a51335b3ff981747a0b2ca2179b96d2c0c9024e5224",
+    "032b80d3a6f5b09f8a84623389c5f80ca69a0cddabc3097f9d9c27310fd43be6e745256c634af45ca3473b0590ae30d1",
+];
+const G1_TIMES_8: [&str; 2] = [
+    "085ae765588126f5e860d019c0e26235f567a9c0c0b2d8ff30f3e8d436b1082596e5e7462d20f5be3764fd473e57f9cf",
+    "19e7dfab8a794b6abb9f84e57739de172a63415273f460d1607fa6a74f0acd97d9671b801dd1fd4f18232dd1259359a1",
+];
+/// On the curve but outside the G1 subgroup
+const G1_NOT_IN_SUBGROUP: [&str; 2] = [
+    "04",
+    "0a989badd40d6212b33cffc3f3763e9bc760f988c9926b26da9dd85e928483446346b8ed00e1de5d5ea93e354abe706c",
+];
+const G1_NOT_IN_SUBGROUP_PLUS_G1: [&str; 2] = [
+    "17bcbbfdd2442c328150f65465bd7b9c4ff36e35261ad3549222e532758a1cf0945ba133ec513517b4ea9de098a037f9",
+    "06d1d4f6580f49b4e0a98509ffd18f24afcada36fd0d44e9fc9e5f0c19df3ec01474eefc659d57d149b97ca899010a5d",
+];
+
+// G2 coordinates are (x.c0, x.c1, y.c0, y.c1)
+const G2: [&str; 4] = [
+    "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+    "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+    "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801",
+    "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be",
+];
+const G2_DOUBLE: [&str; 4] = [
+    "1638533957d540a9d2370f17cc7ed5863bc0b995b8825e0ee1ea1e1e4d00dbae81f14b0bf3611b78c952aacab827a053",
+    "0a4edef9c1ed7f729f520e47730a124fd70662a904ba1074728114d1031e1572c6c886f6b57ec72a6178288c47c33577",
+    "0468fb440d82b0630aeb8dca2b5256789a66da69bf91009cbfe6bd221e47aa8ae88dece9764bf3bd999d95d71e4c9899",
+    "0f6d4552fa65dd2638b361543f887136a43253d9c66c411697003f7a13c308f5422e1aa0a59c8967acdefd8b6e36ccf3",
+];
+const G2_TRIPLE: [&str; 4] = [
+    "122915c824a0857e2ee414a3dccb23ae691ae54329781315a0c75df1c04d6d7a50a030fc866f09d516020ef82324afae",
+    "09380275bbc8e5dcea7dc4dd7e0550ff2ac480905396eda55062650f8d251c96eb480673937cc6d9d6a44aaa56ca66dc",
+    "0b21da7955969e61010c7a1abc1a6f0136961d1e3b20b1a7326ac738fef5c721479dfd948b52fdf2455e44813ecfd892",
+    "08f239ba329b3967fe48d718a36cfe5f62a7e42e0bf1c1ed714150a166bfbd6bcf6b3b58b975b9edea56d53f23a0e849",
+];
+const G2_TIMES_8: [&str; 4] = [
+    "02142a58bae275564a6d63cb6bd6266ca66bef07a6ab8ca37b9d0ba2d4effbccfd89c169649f7d0e8a3eb006846579ad",
+    "12be651a5fa620340d418834526d37a8c932652345400b4cd9d43c8f41c080f41a6d9558118ebeab9d4268bb73e850e1",
+    "15f4b235c209d89ce833f8f296e4cfb748e8abce6990ce1a5a914b9416c08e0d3a26db89625915c821a5f152b7fa592e",
+    "06fcacb3ee6650a1044852d61c9c20bedc8ee90aad97de8e24670a9ef57483e678db11dd95428915088d76e30cb01a37",
+];
+/// On the curve but outside the G2 subgroup
+const G2_NOT_IN_SUBGROUP: [&str; 4] = [
+    "02",
+    "00",
+    "013a59858b6809fca4d9a3b6539246a70051a3c88899964a42bc9a69cf9acdd9dd387cfa9086b894185b9a46a402be73",
+    "02d27e0ec3356299a346a09ad7dc4ef68a483c3aed53f9139d2f929a3eecebf72082e5e58c6da24ee32e03040c406d4f",
+];
+const G2_NOT_IN_SUBGROUP_PLUS_G2: [&str; 4] = [
+    "0458a890e90468ba097a1c7cda15cb596e1b876cc1f879f0588a7f08b18064ffe7482e61dcb30899a6799e29941d12d3",
+    "181d56a9d4d73aad2f8a46208ed0fccd0a4ccbf2cd4ea83d2f64c87111fd0793089000eca5572745c7d45ccea5238cd4",
+    "02aa216e1fa37b0d1117ceb3281b467f3f74d83199b6dc289f51ba2f974c61fefd2384fa3875888733bc50d2a0c71c22",
+    "17af003ce4b523d1868afe1441db47193423d5493e941a114e79c504b1c7f778e063fc85fc631db538b3dc5cbbf3e4a3",
+];
+
+/// Big endian value left padded to `length` bytes
+fn padded(hex: &str, length: usize) -> Vec<u8> {
+    let bytes = hex::decode(format!("{:0>width$}", hex, width = 2 * length)).unwrap();
+    assert_eq!(bytes.len(), length);
+    bytes
+}
+
+/// Field element padded to 64 bytes
+fn fp(hex: &str) -> Vec<u8> {
+    padded(hex, 64)
+}
+
+fn point(coordinates: &[&str]) -> Vec<u8> {
+    coordinates
+        .iter()
+        .flat_map(|coordinate| fp(coordinate))
+        .collect()
+}
+
+fn scalar(hex: &str) -> Vec<u8> {
+    padded(hex, 32)
+}
+
+fn g1_infinity() -> Vec<u8> {
+    vec![0; 128]
+}
+
+fn g2_infinity() -> Vec<u8> {
+    vec![0; 256]
+}
+
+/// Output and gas used by a successful call
+fn run(precompile: u64, input: &[u8]) -> (Vec<u8>, usize) {
+    match run_precompile(precompile, input, &Hardfork::Prague.into()) {
+        (ExecutionResult::Success(ExecutionSuccess::Return(output)), gas) => (output, gas),
+        (result, _) => panic!("precompile {:#x} failed with {:?}", precompile, result),
+    }
+}
+
+fn is_invalid(precompile: u64, input: &[u8]) -> bool {
+    matches!(
+        run_precompile(precompile, input, &Hardfork::Prague.into()).0,
+        ExecutionResult::Error(ExecutionError::InvalidPrecompileInput)
+    )
+}
+
+fn pairing_output(is_one: bool) -> Vec<u8> {
+    let mut output = vec![0; 32];
+    output[31] = is_one as u8;
+    output
+}
+
+#[test]
+fn test_g1_add() {
+    let input = [point(&G1), point(&G1_DOUBLE)].concat();
+    assert_eq!(run(G1ADD, &input), (point(&G1_TRIPLE), 375));
+    let input = [point(&G1), g1_infinity()].concat();
+    assert_eq!(run(G1ADD, &input).0, point(&G1));
+    let input = [point(&G1), point(&G1_NEGATED)].concat();
+    assert_eq!(run(G1ADD, &input).0, g1_infinity());
+    // Addition doesn't check the subgroup
+    let input = [point(&G1_NOT_IN_SUBGROUP), point(&G1)].concat();
+    assert_eq!(run(G1ADD, &input).0, point(&G1_NOT_IN_SUBGROUP_PLUS_G1));
+}
+
+#[test]
+fn test_g1_add_invalid_input() {
+    let valid = [point(&G1), point(&G1_DOUBLE)].concat();
+    assert!(is_invalid(G1ADD, &[]));
+    assert!(is_invalid(G1ADD, &valid[1..]));
+    assert!(is_invalid(G1ADD, &[valid.clone(), vec![0]].concat()));
+    // Coordinates equal to the modulus, or above it but reducing to a point on the curve
+    let input = [fp(P), fp(G1[1]), point(&G1_DOUBLE)].concat();
+    assert!(is_invalid(G1ADD, &input));
+    let above_modulus =
+        "31f2e5916b17be2e71b10b4292f558e727dfd7d48af9cbc5087f0ce00dcca27c8b01e83eaace1aefb539f00adb227166";
+    let input = [fp(above_modulus), fp(G1[1]), point(&G1_DOUBLE)].concat();
+    assert!(is_invalid(G1ADD, &input));
+    // Non zero padding
+    let mut input = valid.clone();
+    input[0] = 1;
+    assert!(is_invalid(G1ADD, &input));
+    // Not on the curve
+    let mut input = valid;
+    input[127] ^= 1;
+    assert!(is_invalid(G1ADD, &input));
+}
+
+#[test]
+fn test_g1_msm() {
+    let input = [point(&G1), scalar("02"), point(&G1_DOUBLE), scalar("03")].concat();
+    assert_eq!(run(G1MSM, &input), (point(&G1_TIMES_8), 22776));
+    // Multiplying by zero or the group order gives the point at infinity
+    assert_eq!(run(G1MSM, &[point(&G1), scalar("00")].concat()), (g1_infinity(), 12000));
+    assert_eq!(run(G1MSM, &[point(&G1), scalar(R)].concat()).0, g1_infinity());
+}
+
+#[test]
+fn test_g1_msm_invalid_input() {
+    let valid = [point(&G1), scalar("02")].concat();
+    assert!(is_invalid(G1MSM, &[]));
+    assert!(is_invalid(G1MSM, &valid[1..]));
+    assert!(is_invalid(G1MSM, &[valid.clone(), vec![0]].concat()));
+    assert!(is_invalid(G1MSM, &[fp(P), fp(G1[1]), scalar("02")].concat()));
+    assert!(is_invalid(G1MSM, &[point(&G1_NOT_IN_SUBGROUP), scalar("02")].concat()));
+    let mut input = valid;
+    input[127] ^= 1;
+    assert!(is_invalid(G1MSM, &input));
+}
+
+#[test]
+fn test_g2_add() {
+    let input = [point(&G2), point(&G2_DOUBLE)].concat();
+    assert_eq!(run(G2ADD, &input), (point(&G2_TRIPLE), 600));
+    let input = [point(&G2), g2_infinity()].concat();
+    assert_eq!(run(G2ADD, &input).0, point(&G2));
+    // Addition doesn't check the subgroup
+    let input = [point(&G2_NOT_IN_SUBGROUP), point(&G2)].concat();
+    assert_eq!(run(G2ADD, &input).0, point(&G2_NOT_IN_SUBGROUP_PLUS_G2));
+}
+
+#[test]
+fn test_g2_add_invalid_input() {
+    let valid = [point(&G2), point(&G2_DOUBLE)].concat();
+    assert!(is_invalid(G2ADD, &[]));
+    assert!(is_invalid(G2ADD, &valid[1..]));
+    assert!(is_invalid(G2ADD, &[valid.clone(), vec![0]].concat()));
+    let input = [point(&[G2[0], P, G2[2], G2[3]]), point(&G2_DOUBLE)].concat();
+    assert!(is_invalid(G2ADD, &input));
+    let mut input = valid;
+    input[255] ^= 1;
+    assert!(is_invalid(G2ADD, &input));
+}
+
+#[test]
+fn test_g2_msm() {
+    let input = [point(&G2), scalar("02"), point(&G2_DOUBLE), scalar("03")].concat();
+    assert_eq!(run(G2MSM, &input), (point(&G2_TIMES_8), 45000));
+    assert_eq!(run(G2MSM, &[point(&G2), scalar("00")].concat()), (g2_infinity(), 22500));
+    assert_eq!(run(G2MSM, &[point(&G2), scalar(R)].concat()).0, g2_infinity());
+}
+
+#[test]
+fn test_g2_msm_invalid_input() {
+    let valid = [point(&G2), scalar("02")].concat();
+    assert!(is_invalid(G2MSM, &[]));
+    assert!(is_invalid(G2MSM, &valid[1..]));
+    assert!(is_invalid(G2MSM, &[valid.clone(), vec![0]].concat()));
+    assert!(is_invalid(G2MSM, &[point(&[P, G2[1], G2[2], G2[3]]), scalar("02")].concat()));
+    assert!(is_invalid(G2MSM, &[point(&G2_NOT_IN_SUBGROUP), scalar("02")].concat()));
+    let mut input = valid;
+    input[255] ^= 1;
+    assert!(is_invalid(G2MSM, &input));
+}
+
+#[test]
+fn test_pairing() {
+    // e(G1, G2) * e(-G1, G2) == 1
+    let input = [point(&G1), point(&G2), point(&G1_NEGATED), point(&G2)].concat();
+    assert_eq!(run(PAIRING, &input), (pairing_output(true), 37700 + 2 * 32600));
+    // e(2 * G1, G2) * e(-G1, 2 * G2) == 1
+    let input = [point(&G1_DOUBLE), point(&G2), point(&G1_NEGATED), point(&G2_DOUBLE)].concat();
+    assert_eq!(run(PAIRING, &input).0, pairing_output(true));
+    let input = [point(&G1), point(&G2)].concat();
+    assert_eq!(run(PAIRING, &input), (pairing_output(false), 37700 + 32600));
+    // Pairings with the point at infinity are one
+    let input = [g1_infinity(), point(&G2)].concat();
+    assert_eq!(run(PAIRING, &input).0, pairing_output(true));
+}
+
+#[test]
+fn test_pairing_invalid_input() {
+    let valid = [point(&G1), point(&G2)].concat();
+    assert!(is_invalid(PAIRING, &[]));
+    assert!(is_invalid(PAIRING, &valid[1..]));
+    assert!(is_invalid(PAIRING, &[valid.clone(), vec![0]].concat()));
+    assert!(is_invalid(PAIRING, &[fp(P), fp(G1[1]), point(&G2)].concat()));
+    assert!(is_invalid(PAIRING, &[point(&G1_NOT_IN_SUBGROUP), point(&G2)].concat()));
+    assert!(is_invalid(PAIRING, &[point(&G1), point(&G2_NOT_IN_SUBGROUP)].concat()));
+    let mut input = valid;
+    input[127] ^= 1;
+    assert!(is_invalid(PAIRING, &input));
+}
+
+#[test]
+fn test_map_fp_to_g1() {
+    let (output, gas) = run(MAP_FP_TO_G1, &fp("01"));
+    assert_eq!(gas, 5500);
+    assert_ne!(output, run(MAP_FP_TO_G1, &fp("02")).0);
+    // The result is in the subgroup, which MSM checks
+    assert_eq!(run(G1MSM, &[output.clone(), scalar("01")].concat()).0, output);
+
+    assert!(is_invalid(MAP_FP_TO_G1, &[]));
+    assert!(is_invalid(MAP_FP_TO_G1, &fp("01")[1..]));
+    assert!(is_invalid(MAP_FP_TO_G1, &[fp("01"), vec![0]].concat()));
+    assert!(is_invalid(MAP_FP_TO_G1, &fp(P)));
+    let mut input = fp("01");
+    input[0] = 1;
+    assert!(is_invalid(MAP_FP_TO_G1, &input));
+}
+
+#[test]
+fn test_map_fp2_to_g2() {
+    let (output, gas) = run(MAP_FP2_TO_G2, &[fp("01"), fp("02")].concat());
+    assert_eq!(gas, 23800);
+    assert_ne!(output, run(MAP_FP2_TO_G2, &[fp("02"), fp("01")].concat()).0);
+    assert_eq!(run(G2MSM, &[output.clone(), scalar("01")].concat()).0, output);
+
+    assert!(is_invalid(MAP_FP2_TO_G2, &[]));
+    assert!(is_invalid(MAP_FP2_TO_G2, &fp("01")));
+    assert!(is_invalid(MAP_FP2_TO_G2, &[fp("01"), fp("02"), vec![0]].concat()));
+    assert!(is_invalid(MAP_FP2_TO_G2, &[fp("01"), fp(P)].concat()));
+}
@@ -0,0 +1,18 @@
+use ethereum_evm::Hardfork;
+use primitive_types::H256;
+
+use super::util::{ address, call, is_success, runtime, storage, CONTRACT };
+
+#[test]
+fn test_call_missing_account_pushes_result() {
+    // SSTORE(0, CALL(gas, 0xee, 0, 0, 0, 0, 0)) SSTORE(1, 1)
+    let code = vec![
+        0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0xee, 0x5a, 0xf1, 0x5f, 0x55, 0x60, 0x01, 0x60, 0x01,
+        0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(1));
+    assert_eq!(storage(&runtime, address(CONTRACT), 1), H256::from_low_u64_be(1));
+}
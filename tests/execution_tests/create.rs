@@ -0,0 +1,154 @@
+use ethereum_evm::{
+    result::{ ExecutionError, ExecutionResult },
+    runtime::Address,
+    util::keccak256,
+    Hardfork,
+};
+use primitive_types::{ H256, U256 };
+
+use super::util::{
+    address,
+    call,
+    is_success,
+    runtime,
+    storage,
+    transact,
+    CONTRACT,
+    GAS_LIMIT,
+    SENDER,
+};
+
+/// One byte more than the EIP-3860 limit
+const OVERSIZED_INIT_CODE: usize = 49153;
+
+#[test]
+fn test_create_address_uses_creating_contract() {
+    // MSTORE8(0, 0x00) CREATE(0, 0, 1) PUSH0 SSTORE, deploys an empty contract
+    let code = vec![0x5f, 0x5f, 0x53, 0x60, 0x01, 0x5f, 0x5f, 0xf0, 0x5f, 0x55, 0x00];
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+
+    // The address depends on the contract executing CREATE rather than the transaction sender
+    let mut stream = rlp::RlpStream::new_list(2);
+    stream.append(&address(CONTRACT));
+    stream.append(&U256::zero());
+    let created = H256::from(Address::from_slice(&keccak256(&stream.out())[12..]));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), created);
+}
+
+#[test]
+fn test_top_level_caller_is_origin() {
+    // CALLER PUSH0 SSTORE
+    let mut runtime = runtime(vec![0x33, 0x5f, 0x55, 0x00]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from(address(SENDER)));
+}
+
+#[test]
+fn test_deploy_code_starting_with_ef() {
+    // MSTORE8(0, 0xef) RETURN(0, 1)
+    let init_code = vec![0x60, 0xef, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+    let mut london = runtime(vec![]);
+    let (result, gas_usage, _) = transact(&mut london, &Hardfork::London.into(), None, &init_code);
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::InvalidCodePrefix)));
+    assert_eq!(gas_usage, GAS_LIMIT as usize);
+
+    // Allowed before EIP-3541
+    let mut berlin = runtime(vec![]);
+    let (result, _, _) = transact(&mut berlin, &Hardfork::Berlin.into(), None, &init_code);
+    assert!(is_success(&result));
+}
+
+#[test]
+fn test_deploy_code_size_limit() {
+    // RETURN(0, 0x6001)
+    let init_code = vec![0x61, 0x60, 0x01, 0x60, 0x00, 0xf3];
+    let mut runtime = runtime(vec![]);
+    let accounts = runtime.contracts.len();
+    let (result, gas_usage, _) = transact(&mut runtime, &Hardfork::London.into(), None, &init_code);
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::CodeSizeExceeded)));
+    assert_eq!(gas_usage, GAS_LIMIT as usize);
+    assert_eq!(runtime.contracts.len(), accounts);
+}
+
+/// CREATE with init code that writes slot 0 twice and then returns code starting with 0xEF
+fn failed_create_gas_usage(second_value: u8) -> usize {
+    let init_code = [
+        0x60, 0x01, 0x60, 0x00, 0x55, 0x60, second_value, 0x60, 0x00, 0x55, 0x60, 0xef, 0x60, 0x00,
+        0x53, 0x60, 0x01, 0x60, 0x00, 0xf3,
+    ];
+    // MSTORE(0, init_code) CREATE(0, 12, 20)
+    let mut code = vec![0x73];
+    code.extend(init_code);
+    code.extend([0x60, 0x00, 0x52, 0x60, 0x14, 0x60, 0x0c, 0x60, 0x00, 0xf0, 0x00]);
+    let mut runtime = runtime(code);
+    let (result, gas_usage, _) = call(&mut runtime, &Hardfork::London.into());
+    assert!(is_success(&result));
+    gas_usage
+}
+
+#[test]
+fn test_failed_create_discards_refunds() {
+    // Resetting the slot to zero earns a refund, which is lost with the failed deployment
+    assert_eq!(failed_create_gas_usage(0x00), failed_create_gas_usage(0x02));
+}
+
+#[test]
+fn test_oversized_create_transaction() {
+    let mut shanghai = runtime(vec![]);
+    let accounts = shanghai.contracts.len();
+    let init_code = vec![0; OVERSIZED_INIT_CODE];
+    let (result, gas_usage, _) = transact(&mut shanghai, &Hardfork::Shanghai.into(), None, &init_code);
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::InitCodeSizeExceeded)));
+    // The transaction is invalid, so it doesn't pay for gas or increase the sender's nonce
+    assert_eq!(gas_usage, 0);
+    assert_eq!(shanghai.contracts[&address(SENDER)].nonce, U256::zero());
+    assert_eq!(shanghai.contracts.len(), accounts);
+
+    // No limit before EIP-3860
+    let mut paris = runtime(vec![]);
+    let (result, _, _) = transact(&mut paris, &Hardfork::Paris.into(), None, &init_code);
+    assert!(is_success(&result));
+}
+
+#[test]
+fn test_create_init_code_size_limit() {
+    // CREATE(0, 0, size) and CREATE2(0, 0, size, 0)
+    let create = |size: u16| vec![0x61, (size >> 8) as u8, size as u8, 0x5f, 0x5f, 0xf0, 0x00];
+    let create2 = |size: u16| {
+        vec![0x5f, 0x61, (size >> 8) as u8, size as u8, 0x5f, 0x5f, 0xf5, 0x00]
+    };
+    for code in [create, create2] {
+        let mut oversized = runtime(code(OVERSIZED_INIT_CODE as u16));
+        let (result, gas_usage, _) = call(&mut oversized, &Hardfork::Shanghai.into());
+        assert!(matches!(result, ExecutionResult::Error(ExecutionError::InitCodeSizeExceeded)));
+        assert_eq!(gas_usage, GAS_LIMIT as usize);
+
+        let mut at_limit = runtime(code(OVERSIZED_INIT_CODE as u16 - 1));
+        let (result, _, _) = call(&mut at_limit, &Hardfork::Shanghai.into());
+        assert!(is_success(&result));
+    }
+}
+
+#[test]
+fn test_init_code_word_cost() {
+    // 2 gas per 32 byte word of init code from Shanghai
+    for (size, cost) in [(32, 2), (33, 4)] {
+        let init_code = vec![0; size];
+        let mut paris = runtime(vec![]);
+        let (_, paris_gas, _) = transact(&mut paris, &Hardfork::Paris.into(), None, &init_code);
+        let mut shanghai = runtime(vec![]);
+        let (_, shanghai_gas, _) = transact(&mut shanghai, &Hardfork::Shanghai.into(), None, &init_code);
+        assert_eq!(shanghai_gas - paris_gas, cost);
+
+        // CREATE(0, 0, size)
+        let code = vec![0x60, size as u8, 0x60, 0x00, 0x60, 0x00, 0xf0, 0x00];
+        let mut paris = runtime(code.clone());
+        let (_, paris_gas, _) = call(&mut paris, &Hardfork::Paris.into());
+        let mut shanghai = runtime(code);
+        let (_, shanghai_gas, _) = call(&mut shanghai, &Hardfork::Shanghai.into());
+        assert_eq!(shanghai_gas - paris_gas, cost);
+    }
+}
@@ -0,0 +1,141 @@
+use ethereum_evm::{
+    execute_transaction,
+    runtime::Address,
+    util::keccak256,
+    Authorization,
+    Hardfork,
+};
+use primitive_types::{ H256, U256 };
+use secp256k1::{ Message, PublicKey, Secp256k1, SecretKey };
+
+use crate::mocks::mock_runtime::MockRuntime;
+
+use super::util::{
+    add_contract,
+    address,
+    call,
+    is_success,
+    runtime,
+    storage,
+    transact,
+    CONTRACT,
+    SENDER,
+};
+
+/// Delegation designator pointing at `target`
+fn designator(target: u64) -> Vec<u8> {
+    let mut code = vec![0xef, 0x01, 0x00];
+    code.extend(address(target).as_bytes());
+    code
+}
+
+#[test]
+fn test_set_code_transaction() {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key).serialize_uncompressed();
+    let authority = Address::from(keccak256(&public_key[1..]));
+
+    // Signed message is MAGIC || rlp([chain_id, address, nonce])
+    let mut stream = rlp::RlpStream::new_list(3);
+    stream.append(&U256::one());
+    stream.append(&address(CONTRACT));
+    stream.append(&U256::zero());
+    let mut message = vec![0x05];
+    message.extend(stream.out());
+    let signature = secp.sign_ecdsa_recoverable(
+        &Message::from_digest(keccak256(&message).0),
+        &secret_key
+    );
+    let (recovery_id, bytes) = signature.serialize_compact();
+    let authorization = Authorization {
+        chain_id: U256::one(),
+        address: address(CONTRACT),
+        nonce: U256::zero(),
+        y_parity: U256::from(i32::from(recovery_id) as u64),
+        r: U256::from_big_endian(&bytes[..32]),
+        s: U256::from_big_endian(&bytes[32..]),
+    };
+
+    // SSTORE(0, 5), runs in the authority's storage
+    let mut runtime = runtime(vec![0x60, 0x05, 0x60, 0x00, 0x55, 0x00]);
+    let (result, gas_usage, _) = execute_transaction(
+        &mut runtime,
+        &Hardfork::Prague.into(),
+        Some(authority),
+        address(SENDER),
+        200_000,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        &[],
+        &[],
+        &[],
+        &[authorization],
+        false
+    ).unwrap();
+    assert!(is_success(&result));
+    assert_eq!(runtime.contracts[&authority].code, designator(CONTRACT));
+    assert_eq!(runtime.contracts[&authority].nonce, U256::one());
+    assert_eq!(storage(&runtime, authority, 0), H256::from_low_u64_be(5));
+    // The authority didn't exist so there is no refund
+    assert_eq!(gas_usage, 21000 + 25000 + 3 + 3 + 22100);
+}
+
+#[test]
+fn test_delegation_to_missing_account() {
+    // SSTORE(0, CALL(gas, 0xdd, 0, 0, 0, 0, 0))
+    let code = vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0xdd, 0x5a, 0xf1, 0x5f, 0x55, 0x00];
+    let mut runtime = runtime(code);
+    add_contract(&mut runtime, address(0xdd), designator(0xee));
+    let (result, _, _) = call(&mut runtime, &Hardfork::Prague.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(1));
+
+    // Calling the delegating account directly also does nothing
+    let (result, _, _) = transact(&mut runtime, &Hardfork::Prague.into(), Some(address(0xdd)), &[]);
+    assert!(is_success(&result));
+}
+
+/// Calls 0xdd delegating to `target` with one byte of input, storing the success flag in slot 0
+/// and the return data size in slot 1, returns the gas used
+fn call_delegation(target: u64) -> (MockRuntime, usize) {
+    // MSTORE8(0, 0x42) SSTORE(0, CALL(gas, 0xdd, 0, 0, 1, 0x20, 0x20)) SSTORE(1, RETURNDATASIZE)
+    let code = vec![
+        0x60, 0x42, 0x5f, 0x53, 0x60, 0x20, 0x60, 0x20, 0x60, 0x01, 0x5f, 0x5f, 0x60, 0xdd, 0x5a,
+        0xf1, 0x5f, 0x55, 0x3d, 0x60, 0x01, 0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    add_contract(&mut runtime, address(0xdd), designator(target));
+    let (result, gas_usage, _) = call(&mut runtime, &Hardfork::Prague.into());
+    assert!(is_success(&result));
+    (runtime, gas_usage)
+}
+
+#[test]
+fn test_delegation_to_precompile() {
+    // Delegating to the identity precompile runs no code rather than the precompile
+    let (precompile, precompile_gas) = call_delegation(0x04);
+    assert_eq!(storage(&precompile, address(CONTRACT), 0), H256::from_low_u64_be(1));
+    assert_eq!(storage(&precompile, address(CONTRACT), 1), H256::zero());
+
+    // Same as delegating to a missing account, except that precompiles are always warm
+    let (missing, missing_gas) = call_delegation(0xee);
+    assert_eq!(storage(&missing, address(CONTRACT), 0), H256::from_low_u64_be(1));
+    assert_eq!(storage(&missing, address(CONTRACT), 1), H256::zero());
+    assert_eq!(missing_gas - precompile_gas, 2600 - 100);
+}
+
+#[test]
+fn test_create_with_designator_init_code() {
+    // MSTORE(0, designator(0xdd)) SSTORE(0, CREATE(0, 9, 23))
+    let mut code = vec![0x76];
+    code.extend(designator(0xdd));
+    code.extend([0x5f, 0x52, 0x60, 0x17, 0x60, 0x09, 0x5f, 0xf0, 0x5f, 0x55, 0x00]);
+    let mut runtime = runtime(code);
+    add_contract(&mut runtime, address(0xdd), vec![0x00]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Prague.into());
+    assert!(is_success(&result));
+    // Init code isn't a delegation, so it fails on the 0xEF byte instead of running 0xdd's code
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::zero());
+}
@@ -0,0 +1,65 @@
+use ethereum_evm::{ result::{ ExecutionError, ExecutionResult }, EVMConfig, Hardfork };
+use primitive_types::H256;
+
+use super::util::{ address, call, is_success, runtime, storage, transact, CONTRACT, GAS_LIMIT };
+
+fn config() -> EVMConfig {
+    let mut config = EVMConfig::new(Hardfork::Prague);
+    config.eof = true;
+    config
+}
+
+/// Builds a container from (inputs, outputs, max stack increase, code) sections
+fn container(sections: &[(u8, u8, u16, Vec<u8>)], data: &[u8]) -> Vec<u8> {
+    let mut code = vec![0xef, 0x00, 0x01, 0x01];
+    code.extend(((sections.len() * 4) as u16).to_be_bytes());
+    code.push(0x02);
+    code.extend((sections.len() as u16).to_be_bytes());
+    for (_, _, _, section) in sections {
+        code.extend((section.len() as u16).to_be_bytes());
+    }
+    code.push(0xff);
+    code.extend((data.len() as u16).to_be_bytes());
+    code.push(0x00);
+    for (inputs, outputs, max_stack_increase, _) in sections {
+        code.extend([*inputs, *outputs]);
+        code.extend(max_stack_increase.to_be_bytes());
+    }
+    for (_, _, _, section) in sections {
+        code.extend(section);
+    }
+    code.extend(data);
+    code
+}
+
+#[test]
+fn test_eof_execution() {
+    // SSTORE(0, CALLF 1 (7)) RJUMPI over INVALID, SSTORE(1, DATALOADN 0)
+    let section0 = vec![
+        0x60, 0x07, 0xe3, 0x00, 0x01, 0x5f, 0x55, 0x60, 0x01, 0xe1, 0x00, 0x01, 0xfe, 0xd1, 0x00,
+        0x00, 0x60, 0x01, 0x55, 0x00,
+    ];
+    // DUP1 ADD RETF
+    let section1 = vec![0x80, 0x01, 0xe4];
+    let code = container(&[(0, 0x80, 2, section0), (1, 1, 1, section1)], &[0x42; 32]);
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &config());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(14));
+    assert_eq!(storage(&runtime, address(CONTRACT), 1), H256::repeat_byte(0x42));
+}
+
+#[test]
+fn test_invalid_eof_is_not_executed() {
+    // RETF in the first section
+    let code = container(&[(0, 0x80, 0, vec![0xe4])], &[]);
+
+    let mut runtime = runtime(code.clone());
+    let (result, gas_usage, _) = call(&mut runtime, &config());
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::InvalidEofContainer)));
+    assert_eq!(gas_usage, GAS_LIMIT as usize);
+
+    // Init code is checked as well
+    let (result, _, _) = transact(&mut runtime, &config(), None, &code);
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::InvalidEofContainer)));
+}
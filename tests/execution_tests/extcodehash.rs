@@ -0,0 +1,24 @@
+use ethereum_evm::{ util::keccak256, Hardfork };
+use primitive_types::H256;
+
+use super::util::{ add_contract, address, call, is_success, runtime, storage, CONTRACT };
+
+#[test]
+fn test_extcodehash() {
+    // SSTORE(0, EXTCODEHASH(CONTRACT)) SSTORE(1, EXTCODEHASH(SENDER)) SSTORE(2, EXTCODEHASH(0xdd))
+    // SSTORE(3, EXTCODEHASH(0xee))
+    let code = vec![
+        0x60, 0xaa, 0x3f, 0x60, 0x00, 0x55, 0x60, 0xbb, 0x3f, 0x60, 0x01, 0x55, 0x60, 0xdd, 0x3f,
+        0x60, 0x02, 0x55, 0x60, 0xee, 0x3f, 0x60, 0x03, 0x55, 0x00,
+    ];
+    let mut runtime = runtime(code.clone());
+    add_contract(&mut runtime, address(0xdd), vec![]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), keccak256(&code));
+    // The sender has a balance but no code
+    assert_eq!(storage(&runtime, address(CONTRACT), 1), keccak256(&[]));
+    // Empty and missing accounts
+    assert_eq!(storage(&runtime, address(CONTRACT), 2), H256::zero());
+    assert_eq!(storage(&runtime, address(CONTRACT), 3), H256::zero());
+}
@@ -0,0 +1,88 @@
+use ethereum_evm::{
+    execute_transaction,
+    result::{ ExecutionError, ExecutionResult },
+    Hardfork,
+};
+use primitive_types::U256;
+
+use crate::mocks::mock_runtime::MockRuntime;
+
+use super::util::{ address, is_success, runtime, CONTRACT, COINBASE, GAS_LIMIT, SENDER };
+
+/// Gas used by a transaction calling a contract that only stops
+const GAS_USAGE: u64 = 21000;
+
+/// Sends a transaction to a contract that only stops with the block's base fee set to `base_fee`
+fn fee_transaction(
+    hardfork: Hardfork,
+    base_fee: u64,
+    max_fee_per_gas: u64,
+    max_priority_fee_per_gas: u64
+) -> (ExecutionResult, usize, MockRuntime) {
+    let mut runtime = runtime(vec![0x00]);
+    runtime.block_base_fee_per_gas = U256::from(base_fee);
+    let (result, gas_usage, _) = execute_transaction(
+        &mut runtime,
+        &hardfork.into(),
+        Some(address(CONTRACT)),
+        address(SENDER),
+        GAS_LIMIT,
+        U256::from(max_fee_per_gas),
+        U256::from(max_priority_fee_per_gas),
+        U256::zero(),
+        &[],
+        &[],
+        &[],
+        &[],
+        false
+    ).unwrap();
+    (result, gas_usage, runtime)
+}
+
+/// Amounts paid by SENDER and received by COINBASE
+fn payments(runtime: &MockRuntime) -> (U256, U256) {
+    (
+        U256::from(u64::MAX) - runtime.contracts[&address(SENDER)].balance,
+        runtime.contracts[&address(COINBASE)].balance,
+    )
+}
+
+#[test]
+fn test_effective_gas_price() {
+    // The sender pays min(max_fee, base_fee + priority_fee), the coinbase only gets the priority
+    // fee and the base fee is burnt
+    let (result, gas_usage, runtime) = fee_transaction(Hardfork::London, 10, 100, 5);
+    assert!(is_success(&result));
+    assert_eq!(gas_usage, GAS_USAGE as usize);
+    assert_eq!(payments(&runtime), (U256::from(GAS_USAGE * 15), U256::from(GAS_USAGE * 5)));
+
+    // Capped by the max fee, which leaves less for the priority fee
+    let (result, _, runtime) = fee_transaction(Hardfork::London, 10, 12, 5);
+    assert!(is_success(&result));
+    assert_eq!(payments(&runtime), (U256::from(GAS_USAGE * 12), U256::from(GAS_USAGE * 2)));
+}
+
+#[test]
+fn test_invalid_gas_price() {
+    // Max fee below the base fee, and priority fee above the max fee
+    for (max_fee, priority_fee) in [(5, 0), (15, 20)] {
+        let (result, gas_usage, runtime) = fee_transaction(Hardfork::London, 10, max_fee, priority_fee);
+        assert!(matches!(result, ExecutionResult::Error(ExecutionError::InvalidGasPrice)));
+        assert_eq!(gas_usage, 0);
+        assert_eq!(payments(&runtime), (U256::zero(), U256::zero()));
+        assert_eq!(runtime.contracts[&address(SENDER)].nonce, U256::zero());
+    }
+}
+
+#[test]
+fn test_gas_price_before_london() {
+    // The gas price is passed as the max fee and all of it goes to the coinbase
+    let (result, _, runtime) = fee_transaction(Hardfork::Berlin, 10, 15, 15);
+    assert!(is_success(&result));
+    assert_eq!(payments(&runtime), (U256::from(GAS_USAGE * 15), U256::from(GAS_USAGE * 15)));
+
+    // The base fee doesn't exist yet, so a lower gas price is still valid
+    let (result, _, runtime) = fee_transaction(Hardfork::Berlin, 10, 5, 5);
+    assert!(is_success(&result));
+    assert_eq!(payments(&runtime), (U256::from(GAS_USAGE * 5), U256::from(GAS_USAGE * 5)));
+}
@@ -0,0 +1,42 @@
+use ethereum_evm::{ Hardfork, Log };
+use primitive_types::H256;
+
+use super::util::{ add_contract, address, call, is_success, runtime, transact, CONTRACT };
+
+#[test]
+fn test_logs_of_reverted_calls_are_discarded() {
+    // MSTORE(0, 0x1234) LOG2(0, 32, 5, 7)
+    // CALL(gas, 0xdd, 0, 0, 0, 0, 0) CALL(gas, 0xee, 0, 0, 0, 0, 0)
+    let code = vec![
+        0x61, 0x12, 0x34, 0x5f, 0x52, 0x60, 0x07, 0x60, 0x05, 0x60, 0x20, 0x5f, 0xa2, 0x5f, 0x5f,
+        0x5f, 0x5f, 0x5f, 0x60, 0xdd, 0x5a, 0xf1, 0x50, 0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0xee,
+        0x5a, 0xf1, 0x50, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    // LOG0(0, 0) REVERT(0, 0)
+    add_contract(&mut runtime, address(0xdd), vec![0x5f, 0x5f, 0xa0, 0x5f, 0x5f, 0xfd]);
+    // LOG1(0, 0, 1)
+    add_contract(&mut runtime, address(0xee), vec![0x60, 0x01, 0x5f, 0x5f, 0xa1, 0x00]);
+    let (result, _, logs) = call(&mut runtime, &Hardfork::Cancun.into());
+    assert!(is_success(&result));
+    let mut data = vec![0; 32];
+    data[30..].copy_from_slice(&[0x12, 0x34]);
+    assert_eq!(logs, vec![
+        Log {
+            address: address(CONTRACT),
+            topics: vec![H256::from_low_u64_be(5), H256::from_low_u64_be(7)],
+            data: data,
+        },
+        Log {
+            address: address(0xee),
+            topics: vec![H256::from_low_u64_be(1)],
+            data: vec![],
+        }
+    ]);
+
+    // A transaction that fails has no logs
+    let config = Hardfork::Cancun.into();
+    let (result, _, logs) = transact(&mut runtime, &config, Some(address(0xdd)), &[]);
+    assert!(!is_success(&result));
+    assert!(logs.is_empty());
+}
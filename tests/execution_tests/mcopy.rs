@@ -0,0 +1,31 @@
+use ethereum_evm::Hardfork;
+use primitive_types::H256;
+
+use super::util::{ address, call, is_success, runtime, storage, CONTRACT };
+
+#[test]
+fn test_mcopy_overlapping() {
+    // MSTORE(0, 0x0102..20) MCOPY(1, 0, 32) SSTORE(0, MLOAD(0))
+    let mut code = vec![0x7f];
+    code.extend(1..=32u8);
+    code.extend([0x5f, 0x52, 0x60, 0x20, 0x5f, 0x60, 0x01, 0x5e, 0x5f, 0x51, 0x5f, 0x55, 0x00]);
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Cancun.into());
+    assert!(is_success(&result));
+    let mut expected = vec![1u8];
+    expected.extend(1..=31u8);
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_slice(&expected));
+}
+
+#[test]
+fn test_empty_mcopy_ignores_offsets() {
+    // MCOPY(1 << 255, 1 << 255, 0) SSTORE(0, 1)
+    let code = vec![
+        0x5f, 0x60, 0x01, 0x60, 0xff, 0x1b, 0x60, 0x01, 0x60, 0xff, 0x1b, 0x5e, 0x60, 0x01, 0x5f,
+        0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Cancun.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(1));
+}
@@ -0,0 +1,21 @@
+pub mod util;
+
+mod access_list;
+mod blobs;
+mod bls12_381;
+mod call;
+mod create;
+mod delegation;
+mod eof;
+mod extcodehash;
+mod fees;
+mod logs;
+mod mcopy;
+mod p256_verify;
+mod precompile_registry;
+mod precompiles;
+mod push0;
+mod refunds;
+mod runtime_errors;
+mod selfdestruct;
+mod transient_storage;
@@ -0,0 +1,100 @@
+use ethereum_evm::{
+    result::{ ExecutionResult, ExecutionSuccess },
+    EVMConfig,
+    Hardfork,
+};
+use p256::ecdsa::{ signature::hazmat::PrehashSigner, Signature, SigningKey };
+
+use super::util::run_precompile;
+
+const P256_VERIFY: u64 = 0x100;
+
+/// Order of the secp256r1 group
+const N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+fn config() -> EVMConfig {
+    let mut config: EVMConfig = Hardfork::Prague.into();
+    config.p256_verify = true;
+    config
+}
+
+/// Input of hash || r || s || x || y for a valid signature
+fn signed_input() -> Vec<u8> {
+    let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+    let hash = [0x22; 32];
+    let signature: Signature = key.sign_prehash(&hash).unwrap();
+    let point = key.verifying_key().to_encoded_point(false);
+    [&hash[..], &signature.to_bytes(), point.x().unwrap(), point.y().unwrap()].concat()
+}
+
+/// Output and gas used, invalid input gives an empty output rather than failing
+fn verify(input: &[u8], config: &EVMConfig) -> (Vec<u8>, usize) {
+    match run_precompile(P256_VERIFY, input, config) {
+        (ExecutionResult::Success(ExecutionSuccess::Return(output)), gas) => (output, gas),
+        // Running the empty account when the precompile is disabled
+        (ExecutionResult::Success(_), gas) => (vec![], gas),
+        (result, _) => panic!("P256VERIFY failed with {:?}", result),
+    }
+}
+
+fn valid() -> Vec<u8> {
+    let mut output = vec![0; 32];
+    output[31] = 1;
+    output
+}
+
+#[test]
+fn test_p256_verify() {
+    let input = signed_input();
+    assert_eq!(verify(&input, &config()), (valid(), 3450));
+    // Signature for another hash
+    let mut input = signed_input();
+    input[0] ^= 1;
+    assert_eq!(verify(&input, &config()), (vec![], 3450));
+}
+
+#[test]
+fn test_p256_verify_invalid_signature_values() {
+    // r and s have to be in [1, n)
+    for offset in [32, 64] {
+        let mut zero = signed_input();
+        zero[offset..offset + 32].fill(0);
+        assert_eq!(verify(&zero, &config()), (vec![], 3450));
+        let mut order = signed_input();
+        order[offset..offset + 32].copy_from_slice(&N);
+        assert_eq!(verify(&order, &config()), (vec![], 3450));
+        let mut max = signed_input();
+        max[offset..offset + 32].fill(0xff);
+        assert_eq!(verify(&max, &config()), (vec![], 3450));
+    }
+}
+
+#[test]
+fn test_p256_verify_invalid_public_key() {
+    // Point at infinity
+    let mut input = signed_input();
+    input[96..].fill(0);
+    assert_eq!(verify(&input, &config()), (vec![], 3450));
+    // Not on the curve
+    let mut input = signed_input();
+    input[159] ^= 1;
+    assert_eq!(verify(&input, &config()), (vec![], 3450));
+}
+
+#[test]
+fn test_p256_verify_input_length() {
+    let input = signed_input();
+    assert_eq!(verify(&[], &config()), (vec![], 3450));
+    assert_eq!(verify(&input[..159], &config()), (vec![], 3450));
+    assert_eq!(verify(&[input, vec![0]].concat(), &config()), (vec![], 3450));
+}
+
+#[test]
+fn test_p256_verify_disabled() {
+    // The address is an empty account unless the precompile is enabled
+    let input = signed_input();
+    assert_eq!(verify(&input, &Hardfork::Prague.into()), (vec![], 0));
+}
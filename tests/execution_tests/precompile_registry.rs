@@ -0,0 +1,96 @@
+use ethereum_evm::{
+    result::{ ExecutionError, ExecutionResult, ExecutionSuccess },
+    runtime::{ Address, Runtime },
+    EVMConfig,
+    Hardfork,
+    Precompile,
+    PrecompileContext,
+    RuntimeError,
+};
+use primitive_types::H256;
+
+use super::util::{ add_contract, address, call, runtime, storage, transact, CONTRACT, SENDER };
+
+/// Counts its calls in slot 0 and returns its caller, reverting if the input is 0xff
+pub struct Counter;
+
+impl Precompile for Counter {
+    fn execute(
+        &self,
+        context: &mut PrecompileContext,
+        runtime: &mut dyn Runtime<Error = RuntimeError>
+    ) -> Result<ExecutionResult, RuntimeError> {
+        if let Err(error) = context.record_gas_usage(1000) {
+            return Ok(ExecutionResult::Error(error));
+        }
+        let address = context.address();
+        let count = runtime.read_storage(address, H256::zero())?.to_low_u64_be() + 1;
+        runtime.set_storage(address, H256::zero(), H256::from_low_u64_be(count))?;
+        if context.input() == [0xff] {
+            return Ok(ExecutionResult::Error(ExecutionError::Revert(vec![])));
+        }
+        let caller = H256::from(context.caller());
+        Ok(ExecutionResult::Success(ExecutionSuccess::Return(caller.as_bytes().to_vec())))
+    }
+}
+
+fn returned_address(result: ExecutionResult) -> Address {
+    match result {
+        ExecutionResult::Success(ExecutionSuccess::Return(data)) => Address::from_slice(&data[12..]),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+fn config() -> EVMConfig {
+    let mut config = EVMConfig::new(Hardfork::Cancun);
+    config.precompiles.insert(address(0x1000), Counter);
+    config.precompiles.insert(address(4), Counter);
+    config.precompiles.remove(address(3));
+    config
+}
+
+#[test]
+fn test_custom_precompile() {
+    // CALL(gas, 0x1000, 0, 0, 0, 0, 32) POP RETURN(0, 32)
+    let code = vec![
+        0x60, 0x20, 0x5f, 0x5f, 0x5f, 0x5f, 0x61, 0x10, 0x00, 0x5a, 0xf1, 0x50, 0x60, 0x20, 0x5f,
+        0xf3,
+    ];
+    let mut runtime = runtime(code);
+    add_contract(&mut runtime, address(0x1000), vec![]);
+    let (result, _, _) = call(&mut runtime, &config());
+    assert_eq!(returned_address(result), address(CONTRACT));
+    assert_eq!(storage(&runtime, address(0x1000), 0), H256::from_low_u64_be(1));
+
+    // Changes are reverted with the call
+    let (result, _, _) = transact(&mut runtime, &config(), Some(address(0x1000)), &[0xff]);
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::Revert(_))));
+    assert_eq!(storage(&runtime, address(0x1000), 0), H256::from_low_u64_be(1));
+}
+
+#[test]
+fn test_replace_built_in_precompile() {
+    let mut runtime = runtime(vec![]);
+    add_contract(&mut runtime, address(4), vec![]);
+    let (result, gas_usage, _) = transact(&mut runtime, &config(), Some(address(4)), &[1]);
+    assert_eq!(returned_address(result), address(SENDER));
+    assert_eq!(gas_usage, 21000 + 16 + 1000);
+}
+
+#[test]
+fn test_remove_built_in_precompile() {
+    // SSTORE(0, CALL(gas, 3, 0, 0, 0, 0, 32)) SSTORE(1, MLOAD(0))
+    let code = vec![
+        0x60, 0x20, 0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0x03, 0x5a, 0xf1, 0x5f, 0x55, 0x5f, 0x51, 0x60,
+        0x01, 0x55, 0x00,
+    ];
+    let mut built_in = runtime(code.clone());
+    call(&mut built_in, &Hardfork::Cancun.into());
+    assert_ne!(storage(&built_in, address(CONTRACT), 1), H256::zero());
+
+    // Without RIPEMD-160 the call succeeds without output, like a call to an empty account
+    let mut removed = runtime(code);
+    call(&mut removed, &config());
+    assert_eq!(storage(&removed, address(CONTRACT), 0), H256::from_low_u64_be(1));
+    assert_eq!(storage(&removed, address(CONTRACT), 1), H256::zero());
+}
@@ -0,0 +1,124 @@
+use ethereum_evm::{ result::{ ExecutionResult, ExecutionSuccess }, Hardfork };
+use primitive_types::{ H256, U256 };
+
+use super::util::{ address, call, is_success, run_precompile, runtime, storage, CONTRACT };
+
+const OUTPUT_OFFSET: u16 = 0x1000;
+
+/// Calls `precompile` with `input` from CONTRACT, returning the call's success flag and the first
+/// word of its output
+fn call_precompile(precompile: u8, input: &[u8], hardfork: Hardfork) -> (H256, H256) {
+    let mut code = vec![];
+    for (index, byte) in input.iter().enumerate().filter(|(_, byte)| **byte != 0) {
+        // MSTORE8(index, byte)
+        code.extend([0x60, *byte, 0x61]);
+        code.extend((index as u16).to_be_bytes());
+        code.push(0x53);
+    }
+    // SSTORE(1, CALL(gas, precompile, 0, 0, len(input), OUTPUT_OFFSET, 32))
+    code.extend([0x60, 0x20, 0x61]);
+    code.extend(OUTPUT_OFFSET.to_be_bytes());
+    code.push(0x61);
+    code.extend((input.len() as u16).to_be_bytes());
+    code.extend([0x60, 0x00, 0x60, 0x00, 0x60, precompile, 0x5a, 0xf1, 0x60, 0x01, 0x55]);
+    // SSTORE(0, MLOAD(OUTPUT_OFFSET))
+    code.push(0x61);
+    code.extend(OUTPUT_OFFSET.to_be_bytes());
+    code.extend([0x51, 0x60, 0x00, 0x55, 0x00]);
+
+    let mut runtime = runtime(code);
+    let (result, _, _) = call(&mut runtime, &hardfork.into());
+    assert!(is_success(&result));
+    (storage(&runtime, address(CONTRACT), 1), storage(&runtime, address(CONTRACT), 0))
+}
+
+fn word(value: U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
+#[test]
+fn test_modexp_from_byzantium() {
+    // 2 ** 3 % 5
+    let mut input = [word(U256::one()), word(U256::one()), word(U256::from(32))].concat();
+    input.extend([2, 3]);
+    input.extend(word(U256::from(5)));
+    let success = H256::from_low_u64_be(1);
+    assert_eq!(
+        call_precompile(0x05, &input, Hardfork::Byzantium),
+        (success, H256::from_low_u64_be(3))
+    );
+    // Before Byzantium this is an empty account
+    assert_eq!(call_precompile(0x05, &input, Hardfork::SpuriousDragon), (success, H256::zero()));
+}
+
+/// x coordinate of twice the generator of alt_bn128
+fn double_generator_x() -> H256 {
+    H256::from_slice(
+        &hex::decode("030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3").unwrap()
+    )
+}
+
+#[test]
+fn test_ecadd_ecmul_from_byzantium() {
+    let generator = [word(U256::one()), word(U256::from(2))].concat();
+    let success = H256::from_low_u64_be(1);
+
+    // G + G
+    let input = [generator.clone(), generator.clone()].concat();
+    assert_eq!(
+        call_precompile(0x06, &input, Hardfork::Byzantium),
+        (success, double_generator_x())
+    );
+    assert_eq!(call_precompile(0x06, &input, Hardfork::SpuriousDragon), (success, H256::zero()));
+
+    // 2 * G
+    let input = [generator, word(U256::from(2))].concat();
+    assert_eq!(
+        call_precompile(0x07, &input, Hardfork::Byzantium),
+        (success, double_generator_x())
+    );
+    assert_eq!(call_precompile(0x07, &input, Hardfork::SpuriousDragon), (success, H256::zero()));
+}
+
+#[test]
+fn test_pairing_from_byzantium() {
+    // An empty product of pairings is one
+    let success = H256::from_low_u64_be(1);
+    assert_eq!(
+        call_precompile(0x08, &[], Hardfork::Byzantium),
+        (success, H256::from_low_u64_be(1))
+    );
+    assert_eq!(call_precompile(0x08, &[], Hardfork::SpuriousDragon), (success, H256::zero()));
+}
+
+#[test]
+fn test_identity() {
+    let input: Vec<u8> = (1..=33).collect();
+    // 15 gas plus 3 per word
+    for (length, gas) in [(0, 15), (1, 18), (32, 18), (33, 21)] {
+        let (result, gas_usage) = run_precompile(0x04, &input[..length], &Hardfork::London.into());
+        match result {
+            ExecutionResult::Success(ExecutionSuccess::Return(output)) => {
+                assert_eq!(output, input[..length]);
+            }
+            result => panic!("identity failed with {:?}", result),
+        }
+        assert_eq!(gas_usage, gas);
+    }
+}
+
+#[test]
+fn test_identity_insufficient_gas() {
+    for (gas, success) in [(17, 0), (18, 1)] {
+        // SSTORE(0, CALL(gas, 0x04, 0, 0, 1, 0, 0))
+        let code = vec![
+            0x5f, 0x5f, 0x60, 0x01, 0x5f, 0x5f, 0x60, 0x04, 0x60, gas, 0xf1, 0x5f, 0x55, 0x00,
+        ];
+        let mut runtime = runtime(code);
+        let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+        assert!(is_success(&result));
+        assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(success));
+    }
+}
@@ -0,0 +1,21 @@
+use ethereum_evm::{ result::{ ExecutionError, ExecutionResult }, Hardfork };
+use primitive_types::H256;
+
+use super::util::{ address, call, is_success, runtime, storage, CONTRACT, GAS_LIMIT };
+
+#[test]
+fn test_push0() {
+    // SSTORE(0, 1) SSTORE(PUSH0, PUSH0)
+    let mut runtime = runtime(vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x5f, 0x5f, 0x55, 0x00]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::zero());
+}
+
+#[test]
+fn test_push0_before_shanghai() {
+    let mut runtime = runtime(vec![0x5f, 0x00]);
+    let (result, gas_usage, _) = call(&mut runtime, &Hardfork::Paris.into());
+    assert!(matches!(result, ExecutionResult::Error(ExecutionError::InvalidOpcode(0x5f))));
+    assert_eq!(gas_usage, GAS_LIMIT as usize);
+}
@@ -0,0 +1,32 @@
+use ethereum_evm::{ runtime::Runtime, Hardfork };
+use primitive_types::H256;
+
+use super::util::{ address, call, runtime, CONTRACT };
+
+/// Runs `code` with slot 0 of CONTRACT already set, returning the gas used
+fn clear_slot(code: Vec<u8>) -> usize {
+    let mut runtime = runtime(code);
+    runtime.contracts
+        .get_mut(&address(CONTRACT))
+        .unwrap()
+        .storage.insert(H256::zero(), H256::from_low_u64_be(1));
+    // Rebuild the context so it sees the committed slot
+    runtime.current_context = None;
+    runtime.add_context().unwrap();
+    let (_, gas_usage, _) = call(&mut runtime, &Hardfork::London.into());
+    gas_usage
+}
+
+#[test]
+fn test_refund_applied_on_success() {
+    // SSTORE(0, 0) STOP
+    let code = vec![0x60, 0x00, 0x60, 0x00, 0x55, 0x00];
+    assert_eq!(clear_slot(code), 26006 - 4800);
+}
+
+#[test]
+fn test_no_refund_on_revert() {
+    // SSTORE(0, 0) REVERT(0, 0)
+    let code = vec![0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0xfd];
+    assert_eq!(clear_slot(code), 26012);
+}
@@ -0,0 +1,125 @@
+use ethereum_evm::{
+    execute_transaction,
+    result::FatalError,
+    runtime::{ Address, Runtime, StorageKey, StorageValue },
+    EVMConfig,
+    Hardfork,
+};
+use primitive_types::{ H256, U256 };
+
+use crate::mocks::mock_runtime::MockRuntime;
+
+use super::precompile_registry::Counter;
+use super::util::{ add_contract, address, runtime, CONTRACT, GAS_LIMIT, SENDER };
+
+/// Runtime whose storage reads fail, everything else is done by the mock runtime
+struct FailingStorage(MockRuntime);
+
+macro_rules! forward {
+    ($(fn $name:ident(&self $(, $arg:ident: $arg_type:ty)*) -> $return_type:ty;)*) => {
+        $(
+            fn $name(&self $(, $arg: $arg_type)*) -> Result<$return_type, String> {
+                Ok(self.0.$name($($arg),*).unwrap())
+            }
+        )*
+    };
+    ($(fn $name:ident(&mut self $(, $arg:ident: $arg_type:ty)*);)*) => {
+        $(
+            fn $name(&mut self $(, $arg: $arg_type)*) -> Result<(), String> {
+                Ok(self.0.$name($($arg),*).unwrap())
+            }
+        )*
+    };
+}
+
+impl Runtime for FailingStorage {
+    type Error = String;
+
+    fn read_storage(&self, _: Address, _: StorageKey) -> Result<StorageValue, String> {
+        Err("storage unavailable".to_string())
+    }
+
+    fn read_original_storage(&self, _: Address, _: StorageKey) -> Result<StorageValue, String> {
+        Err("storage unavailable".to_string())
+    }
+
+    forward! {
+        fn block_hash(&self, block_number: U256) -> H256;
+        fn block_number(&self) -> U256;
+        fn block_coinbase(&self) -> Address;
+        fn block_timestamp(&self) -> U256;
+        fn block_difficulty(&self) -> U256;
+        fn block_randomness(&self) -> U256;
+        fn block_gas_limit(&self) -> U256;
+        fn block_base_fee_per_gas(&self) -> U256;
+        fn block_excess_blob_gas(&self) -> U256;
+        fn chain_id(&self) -> U256;
+        fn balance(&self, address: Address) -> U256;
+        fn code_size(&self, address: Address) -> U256;
+        fn code_hash(&self, address: Address) -> H256;
+        fn code(&self, address: Address) -> Vec<u8>;
+        fn exists(&self, address: Address) -> bool;
+        fn nonce(&self, address: Address) -> U256;
+        fn read_transient_storage(&self, address: Address, index: StorageKey) -> StorageValue;
+        fn is_deleted(&self, address: Address) -> bool;
+        fn is_cold(&self, address: Address) -> bool;
+        fn is_cold_index(&self, address: Address, index: StorageKey) -> bool;
+    }
+
+    forward! {
+        fn mark_hot(&mut self, address: Address);
+        fn mark_hot_index(&mut self, address: Address, index: StorageKey);
+        fn set_storage(&mut self, address: Address, index: StorageKey, value: StorageValue);
+        fn set_transient_storage(&mut self, address: Address, index: StorageKey, value: StorageValue);
+        fn clear_transient_storage(&mut self);
+        fn mark_delete(&mut self, address: Address);
+        fn reset_storage(&mut self, address: Address);
+        fn set_code(&mut self, address: Address, code: Vec<u8>);
+        fn reset_balance(&mut self, address: Address);
+        fn deposit(&mut self, target: Address, value: U256);
+        fn withdrawal(&mut self, source: Address, value: U256);
+        fn increase_nonce(&mut self, address: Address);
+        fn create_contract(&mut self, address: Address, code: Vec<u8>);
+        fn set_contract_code(&mut self, address: Address, code: Vec<u8>);
+        fn add_context(&mut self);
+        fn merge_context(&mut self);
+        fn revert_context(&mut self);
+    }
+}
+
+/// Runs CONTRACT, which calls 0xdd
+fn call_through(config: &EVMConfig, callee_code: Vec<u8>) -> Result<(), FatalError<String>> {
+    // CALL(gas, 0xdd, 0, 0, 0, 0, 0) STOP
+    let mut runtime = runtime(vec![0x5f, 0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0xdd, 0x5a, 0xf1, 0x00]);
+    add_contract(&mut runtime, address(0xdd), callee_code);
+    execute_transaction(
+        &mut FailingStorage(runtime),
+        config,
+        Some(address(CONTRACT)),
+        address(SENDER),
+        GAS_LIMIT,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        &[],
+        &[],
+        &[],
+        &[],
+        false
+    ).map(|_| ())
+}
+
+#[test]
+fn test_runtime_error_in_nested_call() {
+    // SLOAD(0)
+    let result = call_through(&Hardfork::Cancun.into(), vec![0x5f, 0x54, 0x00]);
+    assert_eq!(result, Err(FatalError::Database("storage unavailable".to_string())));
+}
+
+#[test]
+fn test_runtime_error_in_precompile() {
+    let mut config = EVMConfig::new(Hardfork::Cancun);
+    config.precompiles.insert(address(0xdd), Counter);
+    let result = call_through(&config, vec![]);
+    assert_eq!(result, Err(FatalError::Database("storage unavailable".to_string())));
+}
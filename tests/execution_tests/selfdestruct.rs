@@ -0,0 +1,43 @@
+use ethereum_evm::Hardfork;
+use primitive_types::U256;
+
+use super::util::{ address, call, is_success, runtime, transact, CONTRACT };
+
+const BENEFICIARY: u64 = 0xdd;
+
+// SELFDESTRUCT(BENEFICIARY)
+const SELFDESTRUCT: [u8; 3] = [0x60, BENEFICIARY as u8, 0xff];
+
+#[test]
+fn test_selfdestruct_keeps_existing_account() {
+    let mut runtime = runtime(SELFDESTRUCT.to_vec());
+    runtime.current_context
+        .as_mut()
+        .unwrap()
+        .contracts.get_mut(&address(CONTRACT))
+        .unwrap().balance = U256::from(1000);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Cancun.into());
+    assert!(is_success(&result));
+    assert!(runtime.contracts.contains_key(&address(CONTRACT)));
+    assert_eq!(runtime.contracts[&address(CONTRACT)].balance, U256::zero());
+    assert_eq!(runtime.contracts[&address(BENEFICIARY)].balance, U256::from(1000));
+}
+
+#[test]
+fn test_selfdestruct_deletes_account_before_cancun() {
+    let mut runtime = runtime(SELFDESTRUCT.to_vec());
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(is_success(&result));
+    assert!(!runtime.contracts.contains_key(&address(CONTRACT)));
+}
+
+#[test]
+fn test_selfdestruct_deletes_account_created_in_transaction() {
+    let mut runtime = runtime(vec![]);
+    let accounts = runtime.contracts.len();
+    let (result, _, _) = transact(&mut runtime, &Hardfork::Cancun.into(), None, &SELFDESTRUCT);
+    assert!(is_success(&result));
+    // Only the beneficiary is added
+    assert_eq!(runtime.contracts.len(), accounts + 1);
+    assert!(runtime.contracts.contains_key(&address(BENEFICIARY)));
+}
@@ -0,0 +1,46 @@
+use ethereum_evm::Hardfork;
+use primitive_types::H256;
+
+use super::util::{ add_contract, address, call, is_success, runtime, storage, CONTRACT };
+
+#[test]
+fn test_transient_storage_is_cleared_after_transaction() {
+    // SSTORE(1, TLOAD(1)) TSTORE(1, 7) SSTORE(0, TLOAD(1))
+    let code = vec![
+        0x60, 0x01, 0x5c, 0x60, 0x01, 0x55, 0x60, 0x07, 0x60, 0x01, 0x5d, 0x60, 0x01, 0x5c, 0x5f,
+        0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    for _ in 0..2 {
+        let (result, _, _) = call(&mut runtime, &Hardfork::Cancun.into());
+        assert!(is_success(&result));
+        assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::from_low_u64_be(7));
+        // The value stored by the previous transaction is gone
+        assert_eq!(storage(&runtime, address(CONTRACT), 1), H256::zero());
+    }
+    assert!(runtime.transient_storage.is_empty());
+}
+
+#[test]
+fn test_tstore_in_static_call_fails() {
+    // SSTORE(0, STATICCALL(gas, 0xdd, 0, 0, 0, 0)) SSTORE(1, CALL(gas, 0xdd, 0, 0, 0, 0, 0))
+    let code = vec![
+        0x5f, 0x5f, 0x5f, 0x5f, 0x60, 0xdd, 0x5a, 0xfa, 0x5f, 0x55, 0x5f, 0x5f, 0x5f, 0x5f, 0x5f,
+        0x60, 0xdd, 0x5a, 0xf1, 0x60, 0x01, 0x55, 0x00,
+    ];
+    let mut runtime = runtime(code);
+    // TSTORE(1, 7)
+    add_contract(&mut runtime, address(0xdd), vec![0x60, 0x07, 0x60, 0x01, 0x5d, 0x00]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Cancun.into());
+    assert!(is_success(&result));
+    assert_eq!(storage(&runtime, address(CONTRACT), 0), H256::zero());
+    assert_eq!(storage(&runtime, address(CONTRACT), 1), H256::from_low_u64_be(1));
+}
+
+#[test]
+fn test_transient_storage_before_cancun() {
+    // TLOAD(0)
+    let mut runtime = runtime(vec![0x5f, 0x5c, 0x00]);
+    let (result, _, _) = call(&mut runtime, &Hardfork::Shanghai.into());
+    assert!(!is_success(&result));
+}
@@ -0,0 +1,133 @@
+use std::collections::{ BTreeMap, HashSet };
+
+use ethereum_evm::{
+    execute_transaction,
+    result::ExecutionResult,
+    runtime::{ Address, Runtime },
+    util::keccak256,
+    EVMConfig,
+    Log,
+};
+use primitive_types::{ H256, U256 };
+
+use crate::mocks::mock_runtime::{ Contract, MockRuntime };
+
+/// Contract called by the test transactions
+pub const CONTRACT: u64 = 0xaa;
+/// Sender of the test transactions
+pub const SENDER: u64 = 0xbb;
+pub const COINBASE: u64 = 0xcc;
+
+pub const GAS_LIMIT: u64 = 1_000_000;
+
+pub fn address(address: u64) -> Address {
+    Address::from_low_u64_be(address)
+}
+
+pub fn contract(code: Vec<u8>, balance: U256) -> Contract {
+    Contract {
+        balance: balance,
+        code_size: U256::from(code.len()),
+        code_hash: keccak256(&code),
+        code: code,
+        nonce: U256::zero(),
+        storage: BTreeMap::new(),
+        is_deleted: false,
+        is_cold: true,
+        hot_keys: HashSet::new(),
+    }
+}
+
+/// Runtime with `code` at CONTRACT, a funded SENDER and an empty COINBASE
+pub fn runtime(code: Vec<u8>) -> MockRuntime {
+    let mut contracts = BTreeMap::new();
+    contracts.insert(address(CONTRACT), contract(code, U256::zero()));
+    contracts.insert(address(SENDER), contract(vec![], U256::from(u64::MAX)));
+    contracts.insert(address(COINBASE), contract(vec![], U256::zero()));
+    let mut runtime = MockRuntime {
+        block_hashes: BTreeMap::new(),
+        block_number: U256::one(),
+        block_coinbase: address(COINBASE),
+        block_timestamp: U256::one(),
+        block_difficulty: U256::zero(),
+        block_randomness: U256::zero(),
+        block_gas_limit: U256::from(30_000_000),
+        block_base_fee_per_gas: U256::zero(),
+        block_excess_blob_gas: U256::zero(),
+        chain_id: U256::one(),
+        contracts: contracts,
+        transient_storage: BTreeMap::new(),
+        current_context: None,
+    };
+    runtime.add_context().unwrap();
+    runtime
+}
+
+/// Adds an account before the next transaction
+pub fn add_contract(runtime: &mut MockRuntime, address: Address, code: Vec<u8>) {
+    runtime.current_context
+        .as_mut()
+        .unwrap()
+        .contracts.insert(address, contract(code, U256::zero()));
+}
+
+/// Sends a transaction with no value and no fees from SENDER, creating a contract if `to` is None
+pub fn transact(
+    runtime: &mut MockRuntime,
+    config: &EVMConfig,
+    to: Option<Address>,
+    data: &[u8]
+) -> (ExecutionResult, usize, Vec<Log>) {
+    let result = execute_transaction(
+        runtime,
+        config,
+        to,
+        address(SENDER),
+        GAS_LIMIT,
+        U256::zero(),
+        U256::zero(),
+        U256::zero(),
+        data,
+        &[],
+        &[],
+        &[],
+        false
+    ).unwrap();
+    // The mock runtime needs a context for the next transaction
+    runtime.add_context().unwrap();
+    result
+}
+
+/// Calls CONTRACT
+pub fn call(runtime: &mut MockRuntime, config: &EVMConfig) -> (ExecutionResult, usize, Vec<Log>) {
+    transact(runtime, config, Some(address(CONTRACT)), &[])
+}
+
+/// Sends `input` straight to `precompile`, returning the result and the gas used on top of the
+/// intrinsic gas, which is all of the remaining gas if the precompile fails
+pub fn run_precompile(
+    precompile: u64,
+    input: &[u8],
+    config: &EVMConfig
+) -> (ExecutionResult, usize) {
+    let mut runtime = runtime(vec![]);
+    add_contract(&mut runtime, address(precompile), vec![]);
+    let (result, gas_usage, _) = transact(&mut runtime, config, Some(address(precompile)), input);
+    let call_data_cost: usize = input
+        .iter()
+        .map(|byte| if *byte == 0 { 4 } else { 16 })
+        .sum();
+    (result, gas_usage - 21000 - call_data_cost)
+}
+
+/// Committed storage of an account
+pub fn storage(runtime: &MockRuntime, address: Address, key: u64) -> H256 {
+    runtime.contracts[&address].storage
+        .get(&H256::from_low_u64_be(key))
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn is_success(result: &ExecutionResult) -> bool {
+    matches!(result, ExecutionResult::Success(_))
+}
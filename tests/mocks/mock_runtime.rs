@@ -1,10 +1,11 @@
-use ethereum_evm::runtime::Runtime;
-use ethereum_evm::util::{ h256_to_u256, keccak256, u256_to_h256 };
+use ethereum_evm::runtime::{ Address, Runtime, StorageKey, StorageValue };
+use ethereum_evm::util::{ h256_to_u256, keccak256 };
 use hex::encode;
-use primitive_types::{ H160, H256, U256 };
+use primitive_types::{ H256, U256 };
 use rlp::{ Encodable, RlpStream };
 use std::collections::BTreeMap;
 use std::collections::HashSet;
+use std::convert::Infallible;
 use std::mem;
 
 /*
@@ -24,25 +25,28 @@ pub struct Contract {
     pub storage: BTreeMap<H256, H256>,
     pub is_deleted: bool,
     pub is_cold: bool,
-    pub hot_keys: HashSet<U256>,
+    pub hot_keys: HashSet<StorageKey>,
 }
 
 #[derive(Debug)]
 pub struct Context {
     pub prev_context: Option<Box<Context>>,
-    pub contracts: BTreeMap<U256, Contract>,
+    pub contracts: BTreeMap<Address, Contract>,
+    pub transient_storage: BTreeMap<(Address, StorageKey), StorageValue>,
 }
 pub struct MockRuntime {
     pub block_hashes: BTreeMap<U256, H256>,
     pub block_number: U256,
-    pub block_coinbase: U256,
+    pub block_coinbase: Address,
     pub block_timestamp: U256,
     pub block_difficulty: U256,
     pub block_randomness: U256,
     pub block_gas_limit: U256,
     pub block_base_fee_per_gas: U256,
+    pub block_excess_blob_gas: U256,
     pub chain_id: U256,
-    pub contracts: BTreeMap<U256, Contract>,
+    pub contracts: BTreeMap<Address, Contract>,
+    pub transient_storage: BTreeMap<(Address, StorageKey), StorageValue>,
     pub current_context: Option<Box<Context>>,
 }
 
@@ -86,7 +90,7 @@ impl MockRuntime {
             .iter()
             .map(|(address, contract)| {
                 (
-                    H160::from(u256_to_h256(*address)),
+                    *address,
                     {
                         println!("");
                         println!("address: {:x}", address);
@@ -125,161 +129,229 @@ impl MockRuntime {
 }
 
 impl Runtime for MockRuntime {
+    type Error = Infallible;
+
     // Block State
-    fn block_hash(&self, block_number: U256) -> H256 {
-        self.block_hashes[&block_number]
+    fn block_hash(&self, block_number: U256) -> Result<H256, Self::Error> {
+        Ok(self.block_hashes[&block_number])
+    }
+    fn block_number(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_number)
     }
-    fn block_number(&self) -> U256 {
-        self.block_number
+    fn block_coinbase(&self) -> Result<Address, Self::Error> {
+        Ok(self.block_coinbase)
     }
-    fn block_coinbase(&self) -> U256 {
-        self.block_coinbase
+    fn block_timestamp(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_timestamp)
     }
-    fn block_timestamp(&self) -> U256 {
-        self.block_timestamp
+    fn block_difficulty(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_difficulty)
     }
-    fn block_difficulty(&self) -> U256 {
-        self.block_difficulty
+    fn block_randomness(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_randomness)
     }
-    fn block_randomness(&self) -> U256 {
-        self.block_randomness
+    fn block_gas_limit(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_gas_limit)
     }
-    fn block_gas_limit(&self) -> U256 {
-        self.block_gas_limit
+    fn block_base_fee_per_gas(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_base_fee_per_gas)
     }
-    fn block_base_fee_per_gas(&self) -> U256 {
-        self.block_base_fee_per_gas
+    fn block_excess_blob_gas(&self) -> Result<U256, Self::Error> {
+        Ok(self.block_excess_blob_gas)
     }
-    fn chain_id(&self) -> U256 {
-        self.chain_id
+    fn chain_id(&self) -> Result<U256, Self::Error> {
+        Ok(self.chain_id)
     }
 
     // TODO add default values if address is not found
     // Context state
-    fn balance(&self, address: U256) -> U256 {
-        if !self.exists(address) {
-            return U256::from(0 as u64);
+    fn balance(&self, address: Address) -> Result<U256, Self::Error> {
+        if !self.exists(address)? {
+            return Ok(U256::from(0 as u64));
         }
-        self.current_context.as_ref().unwrap().contracts[&address].balance
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].balance)
     }
-    fn code_size(&self, address: U256) -> U256 {
-        if !self.exists(address) {
-            return U256::from(0 as u64);
+    fn code_size(&self, address: Address) -> Result<U256, Self::Error> {
+        if !self.exists(address)? {
+            return Ok(U256::from(0 as u64));
         }
-        self.current_context.as_ref().unwrap().contracts[&address].code_size
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].code_size)
     }
-    fn code_hash(&self, address: U256) -> H256 {
+    fn code_hash(&self, address: Address) -> Result<H256, Self::Error> {
         // if !self.exists(address) {
         //     return H256::from(0 as u64);
         // }
-        self.current_context.as_ref().unwrap().contracts[&address].code_hash
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].code_hash)
     }
-    fn nonce(&self, address: U256) -> U256 {
-        if !self.exists(address) {
-            return U256::from(0 as u64);
+    fn nonce(&self, address: Address) -> Result<U256, Self::Error> {
+        if !self.exists(address)? {
+            return Ok(U256::from(0 as u64));
         }
-        self.current_context.as_ref().unwrap().contracts[&address].nonce
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].nonce)
     }
-    fn code(&self, address: U256) -> Vec<u8> {
-        self.current_context.as_ref().unwrap().contracts[&address].code.clone()
+    fn code(&self, address: Address) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].code.clone())
     }
-    fn exists(&self, address: U256) -> bool {
-        self.current_context.as_ref().unwrap().contracts.contains_key(&address)
+    fn exists(&self, address: Address) -> Result<bool, Self::Error> {
+        Ok(self.current_context.as_ref().unwrap().contracts.contains_key(&address))
     }
 
-    fn read_original_storage(&self, address: U256, index: U256) -> H256 {
-        self.contracts[&address].storage.get(&u256_to_h256(index)).unwrap_or(&H256::zero()).clone()
-    }
-    fn read_storage(&self, address: U256, index: U256) -> H256 {
-        self.current_context
+    fn read_original_storage(
+        &self,
+        address: Address,
+        index: StorageKey
+    ) -> Result<StorageValue, Self::Error> {
+        Ok(match self.contracts.get(&address) {
+            Some(contract) => contract.storage.get(&index).unwrap_or(&H256::zero()).clone(),
+            // Accounts created in this transaction start with empty storage
+            None => H256::zero(),
+        })
+    }
+    fn read_storage(
+        &self,
+        address: Address,
+        index: StorageKey
+    ) -> Result<StorageValue, Self::Error> {
+        Ok(self.current_context
+            .as_ref()
+            .unwrap()
+            .contracts[&address].storage.get(&index)
+            .unwrap_or(&H256::zero())
+            .clone())
+    }
+    fn read_transient_storage(
+        &self,
+        address: Address,
+        index: StorageKey
+    ) -> Result<StorageValue, Self::Error> {
+        Ok(self.current_context
             .as_ref()
             .unwrap()
-            .contracts[&address].storage.get(&u256_to_h256(index))
+            .transient_storage.get(&(address, index))
             .unwrap_or(&H256::zero())
-            .clone()
+            .clone())
     }
 
     // TODO add logic if address is not found
     // Modify Contract State (Should always be valid addresses)
-    fn is_deleted(&self, address: U256) -> bool {
-        self.current_context.as_ref().unwrap().contracts[&address].is_deleted
+    fn is_deleted(&self, address: Address) -> Result<bool, Self::Error> {
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].is_deleted)
     }
-    fn is_cold(&self, address: U256) -> bool {
-        if !self.exists(address) {
-            return true;
+    fn is_cold(&self, address: Address) -> Result<bool, Self::Error> {
+        if !self.exists(address)? {
+            return Ok(true);
         }
-        self.current_context.as_ref().unwrap().contracts[&address].is_cold
+        Ok(self.current_context.as_ref().unwrap().contracts[&address].is_cold)
     }
-    fn is_cold_index(&self, address: U256, index: U256) -> bool {
-        !self.current_context.as_ref().unwrap().contracts[&address].hot_keys.contains(&index)
+    fn is_cold_index(&self, address: Address, index: StorageKey) -> Result<bool, Self::Error> {
+        Ok(!self.current_context.as_ref().unwrap().contracts[&address].hot_keys.contains(&index))
     }
-    fn mark_hot(&mut self, address: U256) {
+    fn mark_hot(&mut self, address: Address) -> Result<(), Self::Error> {
         if !self.current_context.as_mut().unwrap().contracts.contains_key(&address) {
-            return;
+            return Ok(());
         }
         self.current_context.as_mut().unwrap().contracts.get_mut(&address).unwrap().is_cold = false;
+        Ok(())
     }
-    fn mark_hot_index(&mut self, address: U256, index: U256) {
+    fn mark_hot_index(&mut self, address: Address, index: StorageKey) -> Result<(), Self::Error> {
+        if !self.current_context.as_mut().unwrap().contracts.contains_key(&address) {
+            return Ok(());
+        }
         self.current_context
             .as_mut()
             .unwrap()
             .contracts.get_mut(&address)
             .unwrap()
             .hot_keys.insert(index);
-    }
-    fn set_storage(&mut self, address: U256, index: U256, value: H256) {
+        Ok(())
+    }
+    fn set_storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+        value: StorageValue
+    ) -> Result<(), Self::Error> {
         if value == H256::zero() {
             self.current_context
                 .as_mut()
                 .unwrap()
                 .contracts.get_mut(&address)
                 .unwrap()
-                .storage.remove(&u256_to_h256(index));
-            return;
+                .storage.remove(&index);
+            return Ok(());
         }
         self.current_context
             .as_mut()
             .unwrap()
             .contracts.get_mut(&address)
             .unwrap()
-            .storage.insert(u256_to_h256(index), value);
+            .storage.insert(index, value);
         // for (address, contract) in &self.current_context.as_ref().unwrap().contracts {
         // println!("Storage: {:?}", contract.storage);
         // }
+        Ok(())
+    }
+    fn set_transient_storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+        value: StorageValue
+    ) -> Result<(), Self::Error> {
+        let transient_storage = &mut self.current_context.as_mut().unwrap().transient_storage;
+        if value == H256::zero() {
+            transient_storage.remove(&(address, index));
+            return Ok(());
+        }
+        transient_storage.insert((address, index), value);
+        Ok(())
+    }
+    fn clear_transient_storage(&mut self) -> Result<(), Self::Error> {
+        self.transient_storage = BTreeMap::new();
+        if let Some(context) = self.current_context.as_mut() {
+            context.transient_storage = BTreeMap::new();
+        }
+        Ok(())
     }
-    fn mark_delete(&mut self, address: U256) {
+    fn mark_delete(&mut self, address: Address) -> Result<(), Self::Error> {
         self.current_context
             .as_mut()
             .unwrap()
             .contracts.get_mut(&address)
             .unwrap().is_deleted = true;
+        Ok(())
     }
-    fn reset_storage(&mut self, address: U256) {
+    fn reset_storage(&mut self, address: Address) -> Result<(), Self::Error> {
         self.current_context.as_mut().unwrap().contracts.get_mut(&address).unwrap().storage =
             BTreeMap::new();
+        Ok(())
     }
-    fn set_code(&mut self, address: U256, code: Vec<u8>) {
+    fn set_code(&mut self, address: Address, code: Vec<u8>) -> Result<(), Self::Error> {
         self.current_context.as_mut().unwrap().contracts.get_mut(&address).unwrap().code = code;
+        Ok(())
     }
-    fn reset_balance(&mut self, address: U256) {
+    fn reset_balance(&mut self, address: Address) -> Result<(), Self::Error> {
         self.current_context.as_mut().unwrap().contracts.get_mut(&address).unwrap().balance =
             U256::from(0 as u64);
+        Ok(())
     }
-    fn deposit(&mut self, target: U256, value: U256) {
+    fn deposit(&mut self, target: Address, value: U256) -> Result<(), Self::Error> {
         if !self.current_context.as_ref().unwrap().contracts.contains_key(&target) {
-            self.create_contract(target, vec![]);
+            self.create_contract(target, vec![])?;
         }
         self.current_context.as_mut().unwrap().contracts.get_mut(&target).unwrap().balance += value;
+        Ok(())
     }
-    fn withdrawal(&mut self, source: U256, value: U256) {
+    fn withdrawal(&mut self, source: Address, value: U256) -> Result<(), Self::Error> {
         self.current_context.as_mut().unwrap().contracts.get_mut(&source).unwrap().balance -= value;
+        Ok(())
     }
-    fn increase_nonce(&mut self, address: U256) {
+    fn increase_nonce(&mut self, address: Address) -> Result<(), Self::Error> {
         self.current_context.as_mut().unwrap().contracts.get_mut(&address).unwrap().nonce +=
             U256::from(1);
+        Ok(())
     }
 
-    fn create_contract(&mut self, address: U256, code: Vec<u8>) {
+    fn create_contract(&mut self, address: Address, code: Vec<u8>) -> Result<(), Self::Error> {
         println!("Creating Contract");
         let contract = Contract {
             balance: U256::from(0 as u64),
@@ -298,8 +370,9 @@ impl Runtime for MockRuntime {
             }
             None => {}
         };
+        Ok(())
     }
-    fn set_contract_code(&mut self, address: U256, code: Vec<u8>) {
+    fn set_contract_code(&mut self, address: Address, code: Vec<u8>) -> Result<(), Self::Error> {
         match &mut self.current_context {
             Some(context) => {
                 let contract = context.as_mut().contracts.get_mut(&address).unwrap();
@@ -309,15 +382,17 @@ impl Runtime for MockRuntime {
             }
             None => {}
         };
+        Ok(())
     }
     // Modify context stack
-    fn add_context(&mut self) {
+    fn add_context(&mut self) -> Result<(), Self::Error> {
         // Could be slightly faster with a swap perhaps
         match mem::take(&mut self.current_context) {
             Some(context) => {
                 self.current_context = Some(
                     Box::new(Context {
                         contracts: context.contracts.clone(),
+                        transient_storage: context.transient_storage.clone(),
                         prev_context: Some(context),
                     })
                 );
@@ -326,13 +401,15 @@ impl Runtime for MockRuntime {
                 self.current_context = Some(
                     Box::new(Context {
                         contracts: self.contracts.clone(),
+                        transient_storage: self.transient_storage.clone(),
                         prev_context: None,
                     })
                 );
             }
         };
+        Ok(())
     }
-    fn merge_context(&mut self) {
+    fn merge_context(&mut self) -> Result<(), Self::Error> {
         let mut marked_address = vec![];
         {
             let current_context = self.current_context.as_ref().unwrap();
@@ -350,10 +427,12 @@ impl Runtime for MockRuntime {
                 match &mut context.prev_context {
                     Some(prev_context) => {
                         prev_context.contracts = context.contracts;
+                        prev_context.transient_storage = context.transient_storage;
                     }
                     None => {
                         println!("Setting Contracts");
                         self.contracts = context.contracts;
+                        self.transient_storage = context.transient_storage;
                     }
                 }
                 self.current_context = context.prev_context;
@@ -363,8 +442,9 @@ impl Runtime for MockRuntime {
             }
         }
         // iterate through the contracts and remove those that have been deleted
+        Ok(())
     }
-    fn revert_context(&mut self) {
+    fn revert_context(&mut self) -> Result<(), Self::Error> {
         match mem::take(&mut self.current_context) {
             Some(context) => {
                 self.current_context = context.prev_context;
@@ -373,11 +453,12 @@ impl Runtime for MockRuntime {
                 self.current_context = None;
             }
         }
+        Ok(())
     }
 }
 
 impl MockRuntime {
-    fn delete_contract(&mut self, address: &U256) {
+    fn delete_contract(&mut self, address: &Address) {
         self.current_context.as_mut().unwrap().contracts.remove(&address);
     }
 }
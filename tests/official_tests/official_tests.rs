@@ -3,9 +3,14 @@
 // };
 use ethereum_evm::{
     execute_transaction,
+    AccessListItem,
+    Authorization,
+    EVMConfig,
+    Hardfork,
+    logs_hash,
     result::ExecutionResult,
     runtime::Runtime,
-    util::{ keccak256, u256_to_h256 },
+    util::{ keccak256, u256_to_address, u256_to_h256 },
 };
 use primitive_types::U256;
 use serde_json::json;
@@ -51,20 +56,23 @@ pub fn run_test_file(filename: String, debug: bool, index: usize) {
 
 pub fn run_test(test: &TestState, debug: bool) {
     let test = test.clone();
+    // Only tests for supported forks are generated
+    let hardfork = test.fork.parse::<Hardfork>().unwrap();
     let mut runtime = MockRuntime {
         block_hashes: BTreeMap::new(),
         block_number: test.env.current_number,
-        block_coinbase: test.env.current_coinbase,
+        block_coinbase: u256_to_address(test.env.current_coinbase),
         block_timestamp: test.env.current_timestamp,
         block_difficulty: test.env.current_difficulty,
         block_randomness: test.env.current_random,
         block_gas_limit: test.env.current_gas_limit,
         block_base_fee_per_gas: test.env.current_base_fee,
+        block_excess_blob_gas: test.env.current_excess_blob_gas,
         chain_id: U256::zero(),
         contracts: {
             let mut contracts = BTreeMap::new();
             for (address, contract) in &test.pre {
-                contracts.insert(*address, Contract {
+                contracts.insert(u256_to_address(*address), Contract {
                     balance: contract.balance(),
                     code_size: U256::from(contract.code.0.len() as u64),
                     code_hash: keccak256(&contract.code.0),
@@ -79,7 +87,7 @@ pub fn run_test(test: &TestState, debug: bool) {
                     println!("Storage: {:?}", contract.storage().clone());
                 }
             }
-            contracts.insert(test.env.current_coinbase, Contract {
+            contracts.insert(u256_to_address(test.env.current_coinbase), Contract {
                 balance: U256::zero(),
                 code_size: U256::zero(),
                 code_hash: u256_to_h256(U256::zero()),
@@ -92,6 +100,7 @@ pub fn run_test(test: &TestState, debug: bool) {
             });
             contracts
         },
+        transient_storage: BTreeMap::new(),
         current_context: None,
     };
     runtime.add_context();
@@ -99,19 +108,43 @@ pub fn run_test(test: &TestState, debug: bool) {
         println!("Message data size : {}", test.transaction.data.len());
     }
     // Execute the transaction
-    let (result, gas_usage) = execute_transaction(
+    let (result, gas_usage, logs) = execute_transaction(
         &mut runtime,
-        test.transaction.to,
-        test.transaction.sender,
+        &EVMConfig::new(hardfork),
+        test.transaction.to.map(u256_to_address),
+        u256_to_address(test.transaction.sender),
         test.transaction.gas_limit.as_u64(),
-        test.transaction.gas_price.unwrap_or_default(),
+        test.transaction.max_fee_per_gas.or(test.transaction.gas_price).unwrap_or_default(),
+        test.transaction.max_priority_fee_per_gas.or(test.transaction.gas_price).unwrap_or_default(),
         test.transaction.value,
         &test.transaction.data,
+        &test.transaction.blob_versioned_hashes,
+        &test.transaction.access_list
+            .iter()
+            .map(|item| AccessListItem {
+                address: u256_to_address(item.address),
+                storage_keys: item.storage_keys
+                    .iter()
+                    .map(|key| u256_to_h256(*key))
+                    .collect(),
+            })
+            .collect::<Vec<_>>(),
+        &test.transaction.authorization_list
+            .iter()
+            .map(|authorization| Authorization {
+                chain_id: authorization.chain_id,
+                address: u256_to_address(authorization.address),
+                nonce: authorization.nonce,
+                y_parity: authorization.y_parity,
+                r: authorization.r,
+                s: authorization.s,
+            })
+            .collect::<Vec<_>>(),
         debug
-    );
+    ).unwrap();
 
     // Calculate the gas usage
-    let eth_usage = gas_usage * test.transaction.gas_price.unwrap_or_default().as_usize();
+    let eth_usage = U256::from(gas_usage) * test.transaction.gas_price.unwrap_or_default();
     if debug {
         println!("Gas Usage: {}", gas_usage);
         println!("Eth Usage: {}", eth_usage);
@@ -143,6 +176,7 @@ pub fn run_test(test: &TestState, debug: bool) {
     }
     // Debug the balances
     assert_eq!(runtime.state_root_hash(), test.post.hash);
+    assert_eq!(logs_hash(&logs), u256_to_h256(test.post.logs));
 }
 
 // generate_official_tests_from_folder!(